@@ -0,0 +1,254 @@
+use crate::{CELL_SIZE, RoomShell, Rooms, inspector::Selection, ui, walk::Walk};
+use bevy::{
+    camera_controller::free_camera::FreeCameraState,
+    input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll},
+    input_focus::InputFocus,
+    prelude::*,
+    text::EditableText,
+};
+
+/// Smooth camera movements, an orbit mode around the building or the selected
+/// rooms (`O`), preset views (`1` top, `2` front, `3` side, `4` isometric),
+/// framing the selection (`F`) or everything (`Home`).
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Orbit {
+            enabled: false,
+            focus: Vec3::ZERO,
+        })
+        .add_systems(
+            Update,
+            (
+                (toggle_orbit, preset_views).run_if(not(ui::typing)),
+                orbit_camera,
+                fly_camera,
+                enable_free_camera,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// While enabled, dragging with the right mouse button turns the camera around
+/// `focus` and the mouse wheel zooms.
+#[derive(Resource)]
+pub struct Orbit {
+    pub enabled: bool,
+    pub focus: Vec3,
+}
+
+/// Moves the camera smoothly to `translation` while turning it towards `look_at`.
+#[derive(Component)]
+pub struct Flight {
+    pub translation: Vec3,
+    pub look_at: Vec3,
+}
+
+impl Flight {
+    /// Looks at `target` from `distance`, keeping the current viewing direction.
+    pub fn towards(camera: &Transform, target: Vec3, distance: f32) -> Self {
+        Flight {
+            translation: target - camera.forward() * distance,
+            look_at: target,
+        }
+    }
+
+    /// Looks at the box from `min` to `max` from far enough to see all of it.
+    pub fn frame(camera: &Transform, (min, max): (Vec3, Vec3)) -> Self {
+        Flight::towards(camera, (min + max) / 2.0, min.distance(max) * 1.2)
+    }
+}
+
+/// Bounds of the visible rooms with a selected object, or of all visible
+/// rooms if nothing is selected.
+fn focus_bounds(
+    selection: &Selection,
+    rooms: &Rooms,
+    shells: &Query<(&RoomShell, &GlobalTransform, &InheritedVisibility)>,
+) -> Option<(Vec3, Vec3)> {
+    shells
+        .iter()
+        .filter(|(shell, _, visibility)| {
+            visibility.get()
+                && (selection.0.is_empty()
+                    || selection.0.iter().any(|(_, cell)| cell.room == shell.0))
+        })
+        .map(|(shell, transform, _)| {
+            let room = &rooms.0[shell.0];
+            let half_size = Vec3::new(room.width, room.height, room.depth) * CELL_SIZE / 2.0;
+            (
+                transform.translation() - half_size,
+                transform.translation() + half_size,
+            )
+        })
+        .reduce(|(min, max), (other_min, other_max)| (min.min(other_min), max.max(other_max)))
+}
+
+fn toggle_orbit(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut orbit: ResMut<Orbit>,
+    selection: Res<Selection>,
+    rooms: Res<Rooms>,
+    shells: Query<(&RoomShell, &GlobalTransform, &InheritedVisibility)>,
+    cameras: Query<(Entity, &Transform), With<Camera3d>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyO) {
+        return;
+    }
+    orbit.enabled = !orbit.enabled;
+    let Some((min, max)) = focus_bounds(&selection, &rooms, &shells) else {
+        return;
+    };
+    orbit.focus = (min + max) / 2.0;
+    if orbit.enabled {
+        for (entity, transform) in &cameras {
+            let distance = transform.translation.distance(orbit.focus);
+            commands
+                .entity(entity)
+                .insert(Flight::towards(transform, orbit.focus, distance));
+        }
+    }
+}
+
+fn preset_views(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut orbit: ResMut<Orbit>,
+    selection: Res<Selection>,
+    rooms: Res<Rooms>,
+    shells: Query<(&RoomShell, &GlobalTransform, &InheritedVisibility)>,
+    cameras: Query<(Entity, &Transform), With<Camera3d>>,
+) {
+    // The top view is tilted a tiny bit, so the back wall stays at the top.
+    let direction = if keys.just_pressed(KeyCode::Digit1) {
+        Some(Vec3::new(0.0, 1.0, 0.001))
+    } else if keys.just_pressed(KeyCode::Digit2) {
+        Some(Vec3::Z)
+    } else if keys.just_pressed(KeyCode::Digit3) {
+        Some(Vec3::X)
+    } else if keys.just_pressed(KeyCode::Digit4) {
+        Some(Vec3::ONE)
+    } else {
+        None
+    };
+    let bounds = if keys.just_pressed(KeyCode::Home) {
+        focus_bounds(&Selection::default(), &rooms, &shells)
+    } else if direction.is_some() || keys.just_pressed(KeyCode::KeyF) {
+        focus_bounds(&selection, &rooms, &shells)
+    } else {
+        return;
+    };
+    let Some((min, max)) = bounds else {
+        return;
+    };
+    orbit.focus = (min + max) / 2.0;
+    for (entity, transform) in &cameras {
+        let mut flight = Flight::frame(transform, (min, max));
+        if let Some(direction) = direction {
+            let distance = flight.translation.distance(flight.look_at);
+            flight.translation = flight.look_at + direction.normalize() * distance;
+        }
+        commands.entity(entity).insert(flight);
+    }
+}
+
+fn orbit_camera(
+    orbit: Res<Orbit>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    motion: Res<AccumulatedMouseMotion>,
+    scroll: Res<AccumulatedMouseScroll>,
+    mut cameras: Query<(&mut Transform, &Projection), Without<Flight>>,
+) {
+    if !orbit.enabled {
+        return;
+    }
+    for (mut transform, projection) in &mut cameras {
+        // The plan view has its own navigation.
+        if matches!(projection, Projection::Orthographic(_)) {
+            continue;
+        }
+        if buttons.pressed(MouseButton::Right) && motion.delta != Vec2::ZERO {
+            let yaw = Quat::from_rotation_y(-motion.delta.x * 0.005);
+            let pitch = Quat::from_axis_angle(*transform.right(), -motion.delta.y * 0.005);
+            let offset = transform.translation - orbit.focus;
+            let pitched = pitch * offset;
+            // Stop before looking straight up or down.
+            let offset = if pitched.normalize().y.abs() < 0.99 {
+                yaw * pitched
+            } else {
+                yaw * offset
+            };
+            transform.translation = orbit.focus + offset;
+        }
+        if scroll.delta.y != 0.0 {
+            let offset = transform.translation - orbit.focus;
+            let zoom = (1.0 - scroll.delta.y.clamp(-1.0, 1.0) * 0.1).max(0.1);
+            transform.translation = orbit.focus + offset * zoom;
+        }
+        transform.look_at(orbit.focus, Vec3::Y);
+    }
+}
+
+fn fly_camera(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut orbit: ResMut<Orbit>,
+    mut cameras: Query<(
+        Entity,
+        &mut Transform,
+        &Flight,
+        Option<&mut FreeCameraState>,
+    )>,
+) {
+    for (entity, mut transform, flight, state) in &mut cameras {
+        let target =
+            Transform::from_translation(flight.translation).looking_at(flight.look_at, Vec3::Y);
+        transform
+            .translation
+            .smooth_nudge(&target.translation, 6.0, time.delta_secs());
+        transform
+            .rotation
+            .smooth_nudge(&target.rotation, 6.0, time.delta_secs());
+        if transform.translation.distance(target.translation) < 0.001
+            && transform.rotation.angle_between(target.rotation) < 0.001
+        {
+            *transform = target;
+            orbit.focus = flight.look_at;
+            commands.entity(entity).remove::<Flight>();
+        }
+        // The free camera would jump back to its old direction otherwise.
+        if let Some(mut state) = state {
+            let (yaw, pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+            state.yaw = yaw;
+            state.pitch = pitch;
+        }
+    }
+}
+
+/// The free camera is paused in orbit mode, in the plan view, while walking and
+/// while typing.
+fn enable_free_camera(
+    orbit: Res<Orbit>,
+    walk: Res<Walk>,
+    focus: Res<InputFocus>,
+    inputs: Query<(), With<EditableText>>,
+    mut cameras: Query<(&Transform, &Projection, &mut FreeCameraState)>,
+) {
+    let typing = ui::typing(focus, inputs);
+    for (transform, projection, mut state) in &mut cameras {
+        let enabled = !orbit.enabled
+            && !walk.enabled
+            && !typing
+            && matches!(projection, Projection::Perspective(_));
+        if state.enabled != enabled {
+            state.enabled = enabled;
+            // Continue from where the orbit left the camera.
+            let (yaw, pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+            state.yaw = yaw;
+            state.pitch = pitch;
+        }
+    }
+}
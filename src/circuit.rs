@@ -0,0 +1,325 @@
+use crate::{
+    CELL_SIZE, Cell, Metadata, NEIGHBOURS, Object, Room, Rooms, Settings, Wall,
+    inspector::BaseMaterial, lighting::color_temperature, measure::Measure,
+};
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Clicking an object of `type: switch` toggles the lamps wired to it. Lamps
+/// without a switch are always lit.
+///
+/// A lamp is wired to a switch if a chain of `type: wire` objects (or other
+/// switches) connects the two. Every switch flips all lamps it is wired to, so
+/// two switches on the same lamp behave like a two-way (or intermediate)
+/// switching circuit.
+pub struct CircuitPlugin;
+
+impl Plugin for CircuitPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_observer(toggle_switch)
+            .add_systems(Update, light_lamps);
+    }
+}
+
+/// Characters touching each other somewhere in the building.
+#[derive(Resource, Default)]
+pub struct Connections(pub HashMap<char, HashSet<char>>);
+
+impl Connections {
+    pub fn new(rooms: &[Room]) -> Self {
+        let mut connections = Connections::default();
+        for room in rooms {
+            let mut cells: HashMap<IVec3, Vec<char>> = HashMap::new();
+            for (position, ch) in room.surface_cells() {
+                cells.entry(position).or_default().push(ch);
+            }
+            for (position, chars) in &cells {
+                for offset in [IVec3::X, IVec3::Y, IVec3::Z] {
+                    let Some(neighbours) = cells.get(&(*position + offset)) else {
+                        continue;
+                    };
+                    for &a in chars {
+                        for &b in neighbours {
+                            if a != b {
+                                connections.0.entry(a).or_default().insert(b);
+                                connections.0.entry(b).or_default().insert(a);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        connections
+    }
+
+    pub fn of(&self, ch: char) -> impl Iterator<Item = char> + '_ {
+        self.0.get(&ch).into_iter().flatten().copied()
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Circuit {
+    /// Whether a switch is flipped.
+    switches: HashMap<char, bool>,
+    /// Switches wired to each lamp.
+    lamps: HashMap<char, Vec<char>>,
+}
+
+impl Circuit {
+    pub fn new(connections: &Connections, metadata: &Metadata) -> Self {
+        let mut circuit = Circuit::default();
+        for (&ch, _) in metadata.0.iter() {
+            if !metadata.is_type(ch, "switch") {
+                continue;
+            }
+            circuit.switches.insert(ch, false);
+
+            // Follow wires and other switches until reaching lamps.
+            let mut visited = HashSet::from([ch]);
+            let mut queue = vec![ch];
+            while let Some(current) = queue.pop() {
+                for next in connections.of(current) {
+                    if metadata.is_type(next, "lamp") {
+                        let switches = circuit.lamps.entry(next).or_default();
+                        if !switches.contains(&ch) {
+                            switches.push(ch);
+                        }
+                    } else if (metadata.is_type(next, "wire") || metadata.is_type(next, "switch"))
+                        && visited.insert(next)
+                    {
+                        queue.push(next);
+                    }
+                }
+            }
+        }
+        circuit
+    }
+
+    /// Whether a lamp is lit. Lamps without a switch are always lit.
+    pub fn is_lit(&self, lamp: char) -> bool {
+        self.lamps
+            .get(&lamp)
+            .is_none_or(|switches| switches.iter().filter(|ch| self.switches[ch]).count() % 2 == 1)
+    }
+}
+
+/// Marks the lights spawned for lit lamps.
+#[derive(Component)]
+pub struct LampLight;
+
+/// Luminous flux of a lamp without `lumen`.
+const DEFAULT_LUMEN: f32 = 800.0;
+
+/// Distance in metres beyond which a lamp has no effect.
+const LAMP_RANGE: f32 = 20.0;
+
+/// Groups the cells of every lamp character into lamps of touching cells
+/// within a room.
+fn lamp_regions<T: Copy>(rooms: &[Room], cells: &[(char, &Cell, T)]) -> Vec<Vec<T>> {
+    let mut by_position: HashMap<(usize, char, IVec3), usize> = HashMap::new();
+    for (index, &(ch, cell, _)) in cells.iter().enumerate() {
+        let position = rooms[cell.room].surface_position(cell.wall, cell.x, cell.y);
+        by_position.insert((cell.room, ch, position), index);
+    }
+    let mut visited = HashSet::new();
+    let mut regions = Vec::new();
+    for (&(room, ch, start), &index) in &by_position {
+        if !visited.insert(index) {
+            continue;
+        }
+        let mut region = vec![cells[index].2];
+        let mut stack = vec![start];
+        while let Some(position) = stack.pop() {
+            for offset in NEIGHBOURS {
+                let next = position + offset;
+                if let Some(&other) = by_position.get(&(room, ch, next))
+                    && visited.insert(other)
+                {
+                    region.push(cells[other].2);
+                    stack.push(next);
+                }
+            }
+        }
+        regions.push(region);
+    }
+    regions
+}
+
+/// Spawns the light of a lit lamp with its `lumen` and colour temperature
+/// (`kelvin`) in front of the centre of its cells. A lamp with `light: spot`
+/// shines into the room within `angle` degrees. The light is scaled from
+/// metres to the scene.
+fn spawn_lamp_light(
+    commands: &mut Commands,
+    metadata: &Metadata,
+    settings: &Settings,
+    (parent, ch, wall, center): (Entity, char, Wall, Vec3),
+) {
+    let scale = CELL_SIZE / settings.cell_size();
+    let intensity = metadata.number(ch, "lumen").unwrap_or(DEFAULT_LUMEN) * scale * scale;
+    let color = color_temperature(metadata.number(ch, "kelvin").unwrap_or(2700.0));
+    let range = LAMP_RANGE * scale;
+    let into_room = -wall.normal().as_vec3();
+    let up = if into_room.y == 0.0 { Vec3::Y } else { Vec3::Z };
+    let transform =
+        Transform::from_translation(center + into_room * CELL_SIZE * 0.3).looking_to(into_room, up);
+    if metadata.get(ch, "light") == Some("spot") {
+        let angle = metadata.number(ch, "angle").unwrap_or(90.0).to_radians() / 2.0;
+        commands.spawn((
+            LampLight,
+            SpotLight {
+                color,
+                intensity,
+                range,
+                outer_angle: angle,
+                inner_angle: angle * 0.8,
+                ..default()
+            },
+            transform,
+            ChildOf(parent),
+        ));
+    } else {
+        commands.spawn((
+            LampLight,
+            PointLight {
+                color,
+                intensity,
+                range,
+                ..default()
+            },
+            transform,
+            ChildOf(parent),
+        ));
+    }
+}
+
+fn toggle_switch(
+    click: On<Pointer<Click>>,
+    measure: Res<Measure>,
+    mut circuit: ResMut<Circuit>,
+    objects: Query<&Object>,
+) {
+    if measure.enabled {
+        return;
+    }
+    let Ok(object) = objects.get(click.entity) else {
+        return;
+    };
+    if let Some(flipped) = circuit.switches.get_mut(&object.0) {
+        *flipped = !*flipped;
+    }
+}
+
+/// Lets the lit lamps glow and shine, with one light for every lamp. The glow
+/// goes into the own material of the lamp, so it stays while the lamp is
+/// highlighted or dimmed and shows again once its material is restored.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn light_lamps(
+    mut commands: Commands,
+    circuit: Res<Circuit>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rooms: Res<Rooms>,
+    metadata: Res<Metadata>,
+    settings: Res<Settings>,
+    objects: Query<(&Object, &Cell, &Transform, &ChildOf, Option<&BaseMaterial>)>,
+    lights: Query<Entity, With<LampLight>>,
+) {
+    if !circuit.is_changed() {
+        return;
+    }
+    for light in &lights {
+        commands.entity(light).despawn();
+    }
+    let mut lit = Vec::new();
+    for (object, cell, transform, child_of, material) in &objects {
+        if !metadata.is_type(object.0, "lamp") {
+            continue;
+        }
+        let is_lit = circuit.is_lit(object.0);
+        if let Some(mut material) = material.and_then(|material| materials.get_mut(&material.0)) {
+            material.emissive = if is_lit {
+                material.base_color.to_linear() * 20.0
+            } else {
+                LinearRgba::BLACK
+            };
+        }
+        if is_lit {
+            lit.push((
+                object.0,
+                cell,
+                (
+                    object.0,
+                    cell.wall,
+                    transform.translation,
+                    child_of.parent(),
+                ),
+            ));
+        }
+    }
+    for region in lamp_regions(&rooms.0, &lit) {
+        let (ch, wall, _, parent) = region[0];
+        let center = region.iter().map(|cell| cell.2).sum::<Vec3>() / region.len() as f32;
+        spawn_lamp_light(
+            &mut commands,
+            &metadata,
+            &settings,
+            (parent, ch, wall, center),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::empty_room;
+
+    #[test]
+    fn two_way_switching() {
+        let mut room = empty_room(8, 8, 3);
+        // Switches A and B at both ends of wire W, lamp L in the middle and
+        // lamp N without a switch.
+        room.back = vec![
+            (1, 1, 'A'),
+            (2, 1, 'W'),
+            (3, 1, 'W'),
+            (4, 1, 'W'),
+            (5, 1, 'B'),
+            (3, 2, 'L'),
+            (6, 6, 'N'),
+        ];
+        let metadata = Metadata(HashMap::from([
+            (
+                'A',
+                HashMap::from([("type".to_string(), "switch".to_string())]),
+            ),
+            (
+                'B',
+                HashMap::from([("type".to_string(), "switch".to_string())]),
+            ),
+            (
+                'W',
+                HashMap::from([("type".to_string(), "wire".to_string())]),
+            ),
+            (
+                'L',
+                HashMap::from([("type".to_string(), "lamp".to_string())]),
+            ),
+            (
+                'N',
+                HashMap::from([("type".to_string(), "lamp".to_string())]),
+            ),
+        ]));
+        let mut circuit = Circuit::new(&Connections::new(&[room]), &metadata);
+        for (a, b, lit) in [
+            (false, false, false),
+            (true, false, true),
+            (false, true, true),
+            (true, true, false),
+        ] {
+            circuit.switches.insert('A', a);
+            circuit.switches.insert('B', b);
+            assert_eq!(circuit.is_lit('L'), lit, "A {a}, B {b}");
+            assert!(circuit.is_lit('N'));
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use crate::{CELL_SIZE, Cell, Object, RoomShell, Rooms, Wall, surfaces::Surface};
+use bevy::{
+    ecs::system::SystemParam,
+    picking::{
+        hover::HoverMap,
+        mesh_picking::ray_cast::{MeshRayCast, MeshRayCastSettings, RayCastBackfaces},
+    },
+    prelude::*,
+};
+
+/// Shows the room, wall, column and row under the cursor together with the
+/// line and column of the cell in the markdown file.
+pub struct CursorPlugin;
+
+impl Plugin for CursorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, spawn_readout)
+            .add_systems(Update, update_readout);
+    }
+}
+
+/// The wall cell under the mouse cursor.
+#[derive(SystemParam)]
+pub struct CursorCell<'w, 's> {
+    windows: Query<'w, 's, &'static Window>,
+    cameras: Query<'w, 's, (&'static Camera, &'static GlobalTransform)>,
+    ray_cast: MeshRayCast<'w, 's>,
+    rooms: Res<'w, Rooms>,
+    shells: Query<'w, 's, (&'static RoomShell, &'static GlobalTransform)>,
+    objects: Query<'w, 's, &'static Cell, With<Object>>,
+    surfaces: Query<'w, 's, &'static ChildOf, With<Surface>>,
+    hover_map: Res<'w, HoverMap>,
+    nodes: Query<'w, 's, (), With<Node>>,
+}
+
+impl CursorCell<'_, '_> {
+    /// Returns the first object or wall seen from the inside of its room, or
+    /// nothing while the cursor is over a panel.
+    pub fn get(&mut self) -> Option<Cell> {
+        if self
+            .hover_map
+            .values()
+            .flat_map(|hovered| hovered.keys())
+            .any(|&entity| self.nodes.contains(entity))
+        {
+            return None;
+        }
+        let cursor = self.windows.iter().find_map(Window::cursor_position)?;
+        let ray = self
+            .cameras
+            .iter()
+            .find_map(|(camera, transform)| camera.viewport_to_world(transform, cursor).ok())?;
+        let (objects, surfaces, shells, rooms) =
+            (&self.objects, &self.surfaces, &self.shells, &self.rooms);
+        self.ray_cast
+            .cast_ray(ray, &MeshRayCastSettings::default())
+            .iter()
+            .find_map(|(entity, hit)| {
+                if let Ok(cell) = objects.get(*entity) {
+                    return Some(*cell);
+                }
+                let shell = surfaces.get(*entity).map_or(*entity, ChildOf::parent);
+                let (shell, transform) = shells.get(shell).ok()?;
+                if hit.normal.dot(*ray.direction) <= 0.0 {
+                    return None;
+                }
+                let room = &rooms.0[shell.0];
+                let local = transform.affine().inverse().transform_point3(hit.point) / CELL_SIZE;
+                // The wall whose plane the hit is closest to.
+                let size = Vec3::new(room.width, room.height, room.depth);
+                let relative = local / size * 2.0;
+                let axis = relative.abs().max_position();
+                let mut normal = IVec3::ZERO;
+                normal[axis] = relative[axis].signum() as i32;
+                let wall = Wall::facing(normal)?;
+                let (x, y) = room.wall_cell(wall, room.nearest_surface_position(local));
+                Some(Cell {
+                    room: shell.0,
+                    wall,
+                    x,
+                    y,
+                })
+            })
+    }
+}
+
+#[derive(Component)]
+struct Readout;
+
+fn spawn_readout(mut commands: Commands, shells: Query<Entity, With<RoomShell>>) {
+    commands.spawn((
+        Readout,
+        Text::default(),
+        TextShadow::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            bottom: Val::Px(60.0),
+            ..default()
+        },
+    ));
+    // The inside of the walls is what can be seen of the rooms.
+    for shell in &shells {
+        commands.entity(shell).insert(RayCastBackfaces);
+    }
+}
+
+fn update_readout(
+    mut cursor: CursorCell,
+    mut texts: Query<&mut Text, With<Readout>>,
+    mut previous: Local<Option<(usize, Wall, usize, usize)>>,
+) {
+    let cell = cursor.get();
+    let key = cell.map(|cell| (cell.room, cell.wall, cell.x, cell.y));
+    if key == *previous {
+        return;
+    }
+    *previous = key;
+    let readout = cell
+        .map(|cell| {
+            let room = &cursor.rooms.0[cell.room];
+            let (line, column) = room.source_position(cell.wall, cell.x, cell.y);
+            format!(
+                "{}, {} wall, column {}, row {} (line {line}, column {column})",
+                room.name, cell.wall, cell.x, cell.y
+            )
+        })
+        .unwrap_or_default();
+    for mut text in &mut texts {
+        text.0 = readout.clone();
+    }
+}
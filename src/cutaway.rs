@@ -0,0 +1,174 @@
+use crate::{CELL_SIZE, RoomShell, Rooms, inspector::Selection, surfaces::Surface, ui};
+use bevy::{
+    prelude::*,
+    ui_widgets::{SliderRange, SliderValue},
+};
+
+/// Walls between the camera and the rooms of the selected objects are faded
+/// out while the cutaway mode is on (`X`). The slider sets the opacity of all
+/// other room walls.
+pub struct CutawayPlugin;
+
+impl Plugin for CutawayPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Cutaway {
+            enabled: false,
+            opacity: 1.0,
+        })
+        .add_systems(PostStartup, spawn_cutaway_panel)
+        .add_systems(
+            Update,
+            (
+                toggle_cutaway.run_if(not(ui::typing)),
+                read_opacity_slider,
+                fade_walls,
+            )
+                .chain(),
+        );
+    }
+}
+
+/// Opacity of the walls blocking the view in cutaway mode.
+const CUT_OPACITY: f32 = 0.1;
+
+#[derive(Resource)]
+pub struct Cutaway {
+    pub enabled: bool,
+    /// Opacity of the room walls between 0 and 1.
+    pub opacity: f32,
+}
+
+#[derive(Component)]
+struct CutawayText;
+
+#[derive(Component)]
+struct OpacitySlider;
+
+fn spawn_cutaway_panel(mut commands: Commands, tools: Single<Entity, With<ui::Tools>>) {
+    commands
+        .spawn(ui::tool_panel(*tools))
+        .with_children(|parent| {
+            parent.spawn((CutawayText, Text::new("Walls: 100 %, cutaway off (X)")));
+            parent.spawn((OpacitySlider, ui::slider(1.0, SliderRange::new(0.0, 1.0))));
+        });
+}
+
+fn toggle_cutaway(keys: Res<ButtonInput<KeyCode>>, mut cutaway: ResMut<Cutaway>) {
+    if keys.just_pressed(KeyCode::KeyX) {
+        cutaway.enabled = !cutaway.enabled;
+    }
+}
+
+fn read_opacity_slider(
+    mut cutaway: ResMut<Cutaway>,
+    sliders: Query<&SliderValue, (With<OpacitySlider>, Changed<SliderValue>)>,
+    mut texts: Query<&mut Text, With<CutawayText>>,
+) {
+    for value in &sliders {
+        cutaway.opacity = value.0;
+    }
+    if cutaway.is_changed() {
+        for mut text in &mut texts {
+            text.0 = format!(
+                "Walls: {:.0} %, cutaway {} (X)",
+                cutaway.opacity * 100.0,
+                if cutaway.enabled { "on" } else { "off" }
+            );
+        }
+    }
+}
+
+/// Whether the line from `start` to `end` passes through the box at `center`.
+pub fn crosses_box(start: Vec3, end: Vec3, center: Vec3, half_size: Vec3) -> bool {
+    let direction = end - start;
+    let (mut near, mut far) = (0.0_f32, 1.0_f32);
+    for axis in 0..3 {
+        let (min, max) = (
+            center[axis] - half_size[axis],
+            center[axis] + half_size[axis],
+        );
+        if direction[axis].abs() < f32::EPSILON {
+            if start[axis] < min || start[axis] > max {
+                return false;
+            }
+            continue;
+        }
+        let a = (min - start[axis]) / direction[axis];
+        let b = (max - start[axis]) / direction[axis];
+        near = near.max(a.min(b));
+        far = far.min(a.max(b));
+    }
+    near <= far
+}
+
+fn fade_walls(
+    cutaway: Res<Cutaway>,
+    selection: Res<Selection>,
+    rooms: Res<Rooms>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    shells: Query<(
+        Entity,
+        &RoomShell,
+        &GlobalTransform,
+        &MeshMaterial3d<StandardMaterial>,
+    )>,
+    surfaces: Query<(&ChildOf, &MeshMaterial3d<StandardMaterial>), With<Surface>>,
+) {
+    let Ok(camera) = cameras.single() else {
+        return;
+    };
+    let center_of = |index: usize| {
+        shells
+            .iter()
+            .find(|(_, shell, _, _)| shell.0 == index)
+            .map(|(_, _, transform, _)| transform.translation())
+    };
+    let targets: Vec<(usize, Vec3)> = if cutaway.enabled {
+        selection
+            .0
+            .iter()
+            .filter_map(|(_, cell)| Some((cell.room, center_of(cell.room)?)))
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    for (entity, shell, transform, material) in &shells {
+        let room = &rooms.0[shell.0];
+        let half_size = Vec3::new(room.width, room.height, room.depth) * CELL_SIZE / 2.0;
+        let blocks_view = targets.iter().any(|&(index, target)| {
+            index != shell.0
+                && crosses_box(
+                    camera.translation(),
+                    target,
+                    transform.translation(),
+                    half_size,
+                )
+        });
+        let opacity = if blocks_view {
+            CUT_OPACITY.min(cutaway.opacity)
+        } else {
+            cutaway.opacity
+        };
+        let surface_materials = surfaces
+            .iter()
+            .filter(|(child_of, _)| child_of.parent() == entity)
+            .map(|(_, material)| material);
+        for material in std::iter::once(material).chain(surface_materials) {
+            // Only touch the material when needed, as every change re-uploads it.
+            if materials
+                .get(&material.0)
+                .is_some_and(|material| material.base_color.alpha() != opacity)
+                && let Some(mut material) = materials.get_mut(&material.0)
+            {
+                material.base_color.set_alpha(opacity);
+                material.alpha_mode = if opacity < 1.0 {
+                    AlphaMode::Blend
+                } else {
+                    AlphaMode::Opaque
+                };
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use crate::{CELL_SIZE, Cell, Object, Room, Rooms, palette::Colors, ui};
+use bevy::{
+    prelude::*,
+    ui_widgets::{SliderRange, SliderValue},
+};
+use std::collections::HashMap;
+
+/// Pulls the rooms apart from the building centre with a slider. Characters
+/// drawn on the walls of two neighbouring rooms are connected by lines, so it
+/// stays visible which rooms belong together.
+pub struct ExplodePlugin;
+
+impl Plugin for ExplodePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Explode::default())
+            .add_systems(PostStartup, (find_connectors, spawn_explode_panel))
+            .add_systems(Update, (read_explode_slider, draw_connectors));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Explode {
+    /// Distance of every room from the building centre is multiplied by
+    /// `1 + amount`.
+    pub amount: f32,
+    /// Centre of the building in cells.
+    center: Vec3,
+}
+
+impl Explode {
+    /// Offset of a room from its position in the building, in cells.
+    pub fn offset(&self, room: &Room) -> Vec3 {
+        (Vec3::new(room.x, room.y, room.z) - self.center) * self.amount
+    }
+}
+
+/// Pairs of object cells that lie on the same spot of a shared wall.
+#[derive(Resource, Default)]
+struct Connectors(Vec<(Entity, Entity, char)>);
+
+#[derive(Component)]
+struct ExplodeSlider;
+
+fn find_connectors(
+    mut commands: Commands,
+    mut explode: ResMut<Explode>,
+    rooms: Res<Rooms>,
+    objects: Query<(Entity, &Object, &Cell)>,
+) {
+    let (min, max) = rooms.0.iter().fold(
+        (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+        |(min, max), room| {
+            let center = Vec3::new(room.x, room.y, room.z);
+            let half_size = Vec3::new(room.width, room.height, room.depth) / 2.0;
+            (min.min(center - half_size), max.max(center + half_size))
+        },
+    );
+    explode.center = (min + max) / 2.0;
+
+    // Cells of the same character on both sides of a shared wall.
+    let mut spots: HashMap<(IVec3, char), Vec<(Entity, usize)>> = HashMap::new();
+    for (entity, object, cell) in &objects {
+        let room = &rooms.0[cell.room];
+        let position = room.surface_position(cell.wall, cell.x, cell.y);
+        spots
+            .entry((room.wall_spot(cell.wall, position), object.0))
+            .or_default()
+            .push((entity, cell.room));
+    }
+    let mut connectors = Vec::new();
+    for ((_, ch), cells) in spots {
+        for (i, &(a, room_a)) in cells.iter().enumerate() {
+            if let Some(&(b, _)) = cells[i + 1..].iter().find(|(_, room)| *room != room_a) {
+                connectors.push((a, b, ch));
+            }
+        }
+    }
+    commands.insert_resource(Connectors(connectors));
+}
+
+fn spawn_explode_panel(mut commands: Commands, tools: Single<Entity, With<ui::Tools>>) {
+    commands
+        .spawn(ui::tool_panel(*tools))
+        .with_children(|parent| {
+            parent.spawn(Text::new("Explode"));
+            parent.spawn((ExplodeSlider, ui::slider(0.0, SliderRange::new(0.0, 2.0))));
+        });
+}
+
+fn read_explode_slider(
+    mut explode: ResMut<Explode>,
+    sliders: Query<&SliderValue, (With<ExplodeSlider>, Changed<SliderValue>)>,
+) {
+    for value in &sliders {
+        explode.amount = value.0;
+    }
+}
+
+fn draw_connectors(
+    mut gizmos: Gizmos,
+    connectors: Res<Connectors>,
+    colors: Res<Colors>,
+    objects: Query<(&GlobalTransform, &InheritedVisibility)>,
+) {
+    for &(a, b, ch) in &connectors.0 {
+        let Ok([(start, start_visible), (end, end_visible)]) = objects.get_many([a, b]) else {
+            continue;
+        };
+        // Nothing to connect while the rooms touch each other.
+        if start_visible.get()
+            && end_visible.get()
+            && start.translation().distance(end.translation()) > CELL_SIZE
+        {
+            gizmos.line(
+                start.translation(),
+                end.translation(),
+                Color::Hsla(colors.get(ch)),
+            );
+        }
+    }
+}
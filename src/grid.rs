@@ -0,0 +1,129 @@
+use crate::{CELL_SIZE, Room, RoomShell, Rooms, Wall, ui};
+use bevy::prelude::*;
+
+/// Draws the cells of the net on every wall of the shown rooms while the grid
+/// is on (`G`). Every fifth row and column is brighter, counted from the first
+/// row and column of the wall section in the file, and the first cell is
+/// marked with a circle.
+pub struct GridPlugin;
+
+impl Plugin for GridPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Grid(false))
+            .add_systems(PostStartup, spawn_grid_panel)
+            .add_systems(
+                Update,
+                (toggle_grid.run_if(not(ui::typing)), draw_grid).chain(),
+            );
+    }
+}
+
+const LINE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.15);
+const TICK_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.6);
+/// Distance of the grid from the walls towards the inside of the room.
+const LIFT: f32 = 0.01;
+
+/// Whether the grid is drawn.
+#[derive(Resource)]
+struct Grid(bool);
+
+#[derive(Component)]
+struct GridText;
+
+fn spawn_grid_panel(mut commands: Commands, tools: Single<Entity, With<ui::Tools>>) {
+    commands
+        .spawn(ui::tool_panel(*tools))
+        .with_child((GridText, Text::new("Grid (G): off")));
+}
+
+fn toggle_grid(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut grid: ResMut<Grid>,
+    mut texts: Query<&mut Text, With<GridText>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyG) {
+        return;
+    }
+    grid.0 = !grid.0;
+    for mut text in &mut texts {
+        text.0 = format!("Grid (G): {}", if grid.0 { "on" } else { "off" });
+    }
+}
+
+/// Columns and rows of the section of a wall in the net.
+fn section_size(room: &Room, wall: Wall) -> (usize, usize) {
+    let (w, h, d) = (
+        room.width as usize,
+        room.height as usize,
+        room.depth as usize,
+    );
+    match wall {
+        Wall::Top | Wall::Floor => (w, d),
+        Wall::Back | Wall::Front => (w, h),
+        Wall::Right | Wall::Left => (d, h),
+    }
+}
+
+/// Point on a wall at `column` and `row` of its section, counted in cells from
+/// the top left corner, relative to the room centre. The same layout as
+/// `Room::surface_position`, but for the corners of the cells.
+fn section_point(room: &Room, wall: Wall, column: f32, row: f32) -> Vec3 {
+    let (w, h, d) = (room.width, room.height, room.depth);
+    let (u, v) = (column, row);
+    let corner = match wall {
+        Wall::Top => Vec3::new(u, h, d - v),
+        Wall::Back => Vec3::new(u, h - v, 0.0),
+        Wall::Right => Vec3::new(w, h - v, u),
+        Wall::Front => Vec3::new(w - u, h - v, d),
+        Wall::Left => Vec3::new(0.0, h - v, d - u),
+        Wall::Floor => Vec3::new(u, 0.0, v),
+    };
+    corner - Vec3::new(w, h, d) / 2.0
+}
+
+fn draw_grid(
+    mut gizmos: Gizmos,
+    grid: Res<Grid>,
+    rooms: Res<Rooms>,
+    shells: Query<(&RoomShell, &GlobalTransform, &InheritedVisibility)>,
+) {
+    if !grid.0 {
+        return;
+    }
+    for (shell, transform, visibility) in &shells {
+        if !visibility.get() {
+            continue;
+        }
+        let room = &rooms.0[shell.0];
+        for wall in Wall::ALL {
+            let inside = -wall.normal().as_vec3() * LIFT;
+            let point = |column: f32, row: f32| {
+                transform.translation()
+                    + (section_point(room, wall, column, row) + inside) * CELL_SIZE
+            };
+            let (columns, rows) = section_size(room, wall);
+            for column in 0..=columns {
+                let color = if column % 5 == 0 {
+                    TICK_COLOR
+                } else {
+                    LINE_COLOR
+                };
+                let column = column as f32;
+                gizmos.line(point(column, 0.0), point(column, rows as f32), color);
+            }
+            for row in 0..=rows {
+                let color = if row % 5 == 0 { TICK_COLOR } else { LINE_COLOR };
+                let row = row as f32;
+                gizmos.line(point(0.0, row), point(columns as f32, row), color);
+            }
+            gizmos.circle(
+                Isometry3d::new(
+                    point(0.5, 0.5),
+                    Quat::from_rotation_arc(Vec3::Z, wall.normal().as_vec3()),
+                ),
+                CELL_SIZE * 0.3,
+                TICK_COLOR,
+            );
+        }
+    }
+}
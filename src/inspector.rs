@@ -0,0 +1,191 @@
+use crate::{
+    Cell, Descriptions, Object, Rooms, circuit::Connections, measure::Measure, palette::Colors,
+    search::Search, ui,
+};
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Clicking an object selects it and shows its details in a panel until it is
+/// deselected with `Escape`. Hold shift to select several objects.
+pub struct InspectorPlugin;
+
+impl Plugin for InspectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Selection::default())
+            .add_systems(PostStartup, (remember_materials, spawn_inspector))
+            .add_systems(
+                Update,
+                (
+                    clear_selection.run_if(not(ui::typing)),
+                    highlight_objects,
+                    update_inspector,
+                )
+                    .chain(),
+            )
+            .add_observer(select_object);
+    }
+}
+
+/// Selected characters together with the cell they were clicked at.
+#[derive(Resource, Default)]
+pub struct Selection(pub Vec<(char, Cell)>);
+
+impl Selection {
+    pub fn contains(&self, ch: char) -> bool {
+        self.0.iter().any(|(selected, _)| *selected == ch)
+    }
+}
+
+/// Material of an object while it is not highlighted.
+#[derive(Component)]
+pub struct BaseMaterial(pub Handle<StandardMaterial>);
+
+#[derive(Component)]
+struct Inspector;
+
+#[derive(Component)]
+struct InspectorText;
+
+fn remember_materials(
+    mut commands: Commands,
+    objects: Query<(Entity, &MeshMaterial3d<StandardMaterial>), With<Object>>,
+) {
+    for (entity, material) in &objects {
+        commands
+            .entity(entity)
+            .insert(BaseMaterial(material.0.clone()));
+    }
+}
+
+fn spawn_inspector(mut commands: Commands) {
+    commands
+        .spawn((
+            Inspector,
+            ui::panel(Node {
+                right: Val::Px(10.0),
+                top: Val::Px(10.0),
+                max_width: Val::Percent(30.0),
+                display: Display::None,
+                ..default()
+            }),
+        ))
+        .with_child((Text::default(), InspectorText));
+}
+
+fn select_object(
+    click: On<Pointer<Click>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    measure: Res<Measure>,
+    mut selection: ResMut<Selection>,
+    objects: Query<(&Object, &Cell)>,
+) {
+    if click.event.button != PointerButton::Primary || measure.enabled {
+        return;
+    }
+    let Ok((object, cell)) = objects.get(click.entity) else {
+        return;
+    };
+    if !keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+        selection.0 = vec![(object.0, *cell)];
+    } else if selection.contains(object.0) {
+        selection.0.retain(|(selected, _)| *selected != object.0);
+    } else {
+        selection.0.push((object.0, *cell));
+    }
+}
+
+fn clear_selection(keys: Res<ButtonInput<KeyCode>>, mut selection: ResMut<Selection>) {
+    if keys.just_pressed(KeyCode::Escape) && !selection.0.is_empty() {
+        selection.0.clear();
+    }
+}
+
+/// Highlights selected objects and search hits and dims everything else while
+/// searching.
+fn highlight_objects(
+    selection: Res<Selection>,
+    search: Res<Search>,
+    colors: Res<Colors>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut objects: Query<(
+        &Object,
+        &BaseMaterial,
+        &mut MeshMaterial3d<StandardMaterial>,
+    )>,
+    mut highlights: Local<HashMap<char, Handle<StandardMaterial>>>,
+    mut dimmed: Local<Option<Handle<StandardMaterial>>>,
+) {
+    if !selection.is_changed() && !search.is_changed() {
+        return;
+    }
+    let dimmed = dimmed
+        .get_or_insert_with(|| {
+            materials.add(StandardMaterial {
+                base_color: Color::srgba(0.5, 0.5, 0.5, 0.2),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })
+        })
+        .clone();
+    for (object, base, mut material) in &mut objects {
+        if selection.contains(object.0) || search.matches(object.0) {
+            let highlight = highlights.entry(object.0).or_insert_with(|| {
+                materials.add(Color::Hsla(Hsla {
+                    lightness: 0.8,
+                    ..colors.get(object.0)
+                }))
+            });
+            material.0 = highlight.clone();
+        } else if search.is_active() {
+            material.0 = dimmed.clone();
+        } else if highlights.get(&object.0) == Some(&material.0) || material.0 == dimmed {
+            material.0 = base.0.clone();
+        }
+    }
+}
+
+fn update_inspector(
+    selection: Res<Selection>,
+    descriptions: Res<Descriptions>,
+    rooms: Res<Rooms>,
+    connections: Res<Connections>,
+    mut panels: Query<&mut Node, With<Inspector>>,
+    mut texts: Query<&mut Text, With<InspectorText>>,
+) {
+    if !selection.is_changed() && !descriptions.is_changed() {
+        return;
+    }
+    let mut details = Vec::new();
+    for (ch, cell) in &selection.0 {
+        let mut connected: Vec<String> = connections.of(*ch).map(String::from).collect();
+        connected.sort();
+        details.push(format!(
+            "Object: {ch}\n{}\nRoom: {}\nWall: {}, column {}, row {}\nConnected to: {}",
+            descriptions
+                .0
+                .get(ch)
+                .map(String::as_str)
+                .unwrap_or("No description available."),
+            rooms.0[cell.room].name,
+            cell.wall,
+            cell.x,
+            cell.y,
+            if connected.is_empty() {
+                "nothing".to_string()
+            } else {
+                connected.join(", ")
+            },
+        ));
+    }
+    for mut node in &mut panels {
+        node.display = if details.is_empty() {
+            Display::None
+        } else {
+            Display::Flex
+        };
+    }
+    for mut text in &mut texts {
+        text.0 = details.join("\n\n");
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    CELL_SIZE, RoomShell, Rooms, circuit::Connections, inspector::Selection, levels::Levels, ui,
+};
+use bevy::{prelude::*, ui::Checked};
+use std::collections::HashSet;
+
+/// Shows only the objects of the selected characters while the isolate mode is
+/// on (`I`), optionally together with the objects they touch. The rooms are
+/// drawn as faint outlines, so a single wire can be followed through the house.
+pub struct IsolatePlugin;
+
+impl Plugin for IsolatePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Isolate::default())
+            .add_systems(PostStartup, spawn_isolate_panel)
+            .add_systems(
+                Update,
+                (
+                    toggle_isolate.run_if(not(ui::typing)),
+                    read_connected_checkbox,
+                    update_isolated,
+                    draw_room_outlines,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Colour of the room outlines in isolate mode.
+const OUTLINE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.15);
+
+#[derive(Resource, Default)]
+pub struct Isolate {
+    pub enabled: bool,
+    /// Whether the objects touching the selected ones are shown as well.
+    pub connected: bool,
+    /// Characters shown, empty while nothing is selected.
+    pub characters: HashSet<char>,
+}
+
+impl Isolate {
+    /// Whether the rest of the building is hidden.
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.characters.is_empty()
+    }
+}
+
+#[derive(Component)]
+struct IsolateText;
+
+#[derive(Component)]
+struct ConnectedToggle;
+
+fn spawn_isolate_panel(mut commands: Commands, tools: Single<Entity, With<ui::Tools>>) {
+    let panel = commands
+        .spawn(ui::tool_panel(*tools))
+        .with_child((IsolateText, Text::new("Isolate (I): off")))
+        .id();
+    commands.spawn((
+        Node {
+            column_gap: Val::Px(6.0),
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        ChildOf(panel),
+        children![
+            (ConnectedToggle, ui::checkbox(Color::WHITE)),
+            Text::new("With connected objects"),
+        ],
+    ));
+}
+
+fn toggle_isolate(keys: Res<ButtonInput<KeyCode>>, mut isolate: ResMut<Isolate>) {
+    if keys.just_pressed(KeyCode::KeyI) {
+        isolate.enabled = !isolate.enabled;
+    }
+}
+
+fn read_connected_checkbox(
+    mut isolate: ResMut<Isolate>,
+    toggles: Query<Has<Checked>, With<ConnectedToggle>>,
+) {
+    for checked in &toggles {
+        if checked != isolate.connected {
+            isolate.connected = checked;
+        }
+    }
+}
+
+fn update_isolated(
+    mut isolate: ResMut<Isolate>,
+    selection: Res<Selection>,
+    connections: Res<Connections>,
+    mut texts: Query<&mut Text, With<IsolateText>>,
+) {
+    if !isolate.is_changed() && !selection.is_changed() {
+        return;
+    }
+    let mut characters = HashSet::new();
+    if isolate.enabled {
+        for &(ch, _) in &selection.0 {
+            characters.insert(ch);
+            if isolate.connected {
+                characters.extend(connections.of(ch));
+            }
+        }
+    }
+    if characters != isolate.characters {
+        isolate.characters = characters;
+    }
+    for mut text in &mut texts {
+        text.0 = if !isolate.enabled {
+            "Isolate (I): off".to_string()
+        } else if isolate.characters.is_empty() {
+            "Isolate (I): select an object".to_string()
+        } else {
+            let mut characters: Vec<String> =
+                isolate.characters.iter().map(char::to_string).collect();
+            characters.sort();
+            format!("Isolate (I): {}", characters.join(", "))
+        };
+    }
+}
+
+fn draw_room_outlines(
+    mut gizmos: Gizmos,
+    isolate: Res<Isolate>,
+    levels: Res<Levels>,
+    rooms: Res<Rooms>,
+    shells: Query<(&RoomShell, &GlobalTransform)>,
+) {
+    if !isolate.is_active() {
+        return;
+    }
+    for (shell, transform) in &shells {
+        if !levels.is_shown(shell.0) {
+            continue;
+        }
+        let room = &rooms.0[shell.0];
+        gizmos.cube(
+            Transform::from_translation(transform.translation())
+                .with_scale(Vec3::new(room.width, room.height, room.depth) * CELL_SIZE),
+            OUTLINE_COLOR,
+        );
+    }
+}
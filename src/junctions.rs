@@ -0,0 +1,220 @@
+use crate::{CELL_SIZE, Metadata, NEIGHBOURS, Room, RoomShell, Rooms, SurfaceGraph, Wall, ui};
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+/// Finds the cells where a wire forks into three or more directions, which is
+/// where a junction box is needed, and wire ends that do not touch any object.
+/// Every wire character is followed on its own, also through shared walls.
+/// Both are listed per room at startup and can be marked in the scene with `J`.
+pub struct JunctionPlugin;
+
+impl Plugin for JunctionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, spawn_junction_markers)
+            .add_systems(Update, toggle_junction_markers.run_if(not(ui::typing)));
+    }
+}
+
+#[derive(Default)]
+pub struct Junctions {
+    /// Wire cells with three or more neighbours of the same wire.
+    pub branches: Vec<(Wall, usize, usize)>,
+    /// Wire cells with at most one neighbour of the same wire and no other
+    /// object next to them.
+    pub open_ends: Vec<(Wall, usize, usize)>,
+}
+
+impl Junctions {
+    /// Junctions of every room. A wire drawn on both sides of a wall shared by
+    /// two rooms continues through the wall.
+    pub fn new(rooms: &[Room], metadata: &Metadata) -> Vec<Self> {
+        let graph = SurfaceGraph::new(rooms);
+        let has_partner =
+            |room: usize, position: IVec3, ch: char| !graph.partners(room, position, ch).is_empty();
+        let graph = &graph;
+        let same_wire = move |room: usize, position: IVec3, ch: char| {
+            NEIGHBOURS
+                .iter()
+                .map(move |offset| position + offset)
+                .filter(move |next| graph.chars(room, *next).contains(&ch))
+        };
+
+        let mut junctions: Vec<Junctions> = rooms.iter().map(|_| Junctions::default()).collect();
+        let mut visited = HashSet::new();
+        for (index, room) in rooms.iter().enumerate() {
+            for (wall, objects) in room.walls() {
+                for &(x, y, ch) in objects {
+                    let position = room.surface_position(wall, x, y);
+                    if !metadata.is_type(ch, "wire") || !visited.insert((index, position, ch)) {
+                        continue;
+                    }
+                    let mut degree = same_wire(index, position, ch).count();
+                    // The wire goes on in the other room, unless it only runs
+                    // along both sides of the wall.
+                    let through =
+                        graph
+                            .partners(index, position, ch)
+                            .iter()
+                            .any(|&(other, point)| {
+                                same_wire(other, point, ch)
+                                    .any(|next| !has_partner(other, next, ch))
+                            });
+                    if through {
+                        degree += 1;
+                    }
+                    let touches_object = NEIGHBOURS
+                        .iter()
+                        .flat_map(|offset| graph.chars(index, position + offset))
+                        .any(|&other| other != ch && !metadata.is_type(other, "wire"));
+                    if degree >= 3 {
+                        junctions[index].branches.push((wall, x, y));
+                    } else if degree <= 1 && !touches_object && !has_partner(index, position, ch) {
+                        junctions[index].open_ends.push((wall, x, y));
+                    }
+                }
+            }
+        }
+        junctions
+    }
+}
+
+#[derive(Component)]
+struct JunctionMarker;
+
+fn spawn_junction_markers(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    rooms: Res<Rooms>,
+    metadata: Res<Metadata>,
+    shells: Query<(Entity, &RoomShell)>,
+) {
+    let mesh = meshes.add(Sphere::new(CELL_SIZE * 0.4));
+    let branch_material = materials.add(StandardMaterial {
+        base_color: Color::WHITE,
+        unlit: true,
+        ..default()
+    });
+    let open_end_material = materials.add(StandardMaterial {
+        base_color: Color::BLACK,
+        unlit: true,
+        ..default()
+    });
+    let mut shells: Vec<_> = shells.iter().collect();
+    shells.sort_by_key(|(_, shell)| shell.0);
+    let all_junctions = Junctions::new(&rooms.0, &metadata);
+    let mut boxes = 0;
+    for (entity, shell) in shells {
+        let room = &rooms.0[shell.0];
+        let junctions = &all_junctions[shell.0];
+        for (wall, x, y) in &junctions.branches {
+            println!(
+                "{}: junction box on the {wall} wall at column {x}, row {y}.",
+                room.name
+            );
+        }
+        for (wall, x, y) in &junctions.open_ends {
+            println!(
+                "{}: open wire end on the {wall} wall at column {x}, row {y}.",
+                room.name
+            );
+        }
+        boxes += junctions.branches.len();
+
+        let markers = junctions
+            .branches
+            .iter()
+            .map(|cell| (cell, branch_material.clone()))
+            .chain(
+                junctions
+                    .open_ends
+                    .iter()
+                    .map(|cell| (cell, open_end_material.clone())),
+            );
+        commands.entity(entity).with_children(|parent| {
+            for (&(wall, x, y), material) in markers {
+                let position = room.surface_position(wall, x, y);
+                parent.spawn((
+                    JunctionMarker,
+                    Mesh3d(mesh.clone()),
+                    MeshMaterial3d(material),
+                    Transform::from_translation(room.local_position(position) * CELL_SIZE),
+                    Visibility::Hidden,
+                    Pickable::IGNORE,
+                    bevy::light::NotShadowCaster,
+                ));
+            }
+        });
+    }
+    println!("{boxes} junction boxes needed.");
+}
+
+fn toggle_junction_markers(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut markers: Query<&mut Visibility, With<JunctionMarker>>,
+) {
+    if keys.just_pressed(KeyCode::KeyJ) {
+        for mut visibility in &mut markers {
+            visibility.toggle_visible_hidden();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::empty_room;
+    use std::collections::HashMap;
+
+    fn wires(chars: &[char]) -> Metadata {
+        Metadata(
+            chars
+                .iter()
+                .map(|&ch| {
+                    let kind = if ch == 'L' { "lamp" } else { "wire" };
+                    (ch, HashMap::from([("type".to_string(), kind.to_string())]))
+                })
+                .collect(),
+        )
+    }
+
+    fn sorted(mut cells: Vec<(Wall, usize, usize)>) -> Vec<(usize, usize)> {
+        cells.sort_by_key(|&(_, x, y)| (x, y));
+        cells.into_iter().map(|(_, x, y)| (x, y)).collect()
+    }
+
+    #[test]
+    fn t_shaped_wire() {
+        let mut room = empty_room(8, 8, 3);
+        // Wire W forks at (2, 1) and its right end touches lamp L.
+        room.back = vec![
+            (1, 1, 'W'),
+            (2, 1, 'W'),
+            (3, 1, 'W'),
+            (2, 2, 'W'),
+            (2, 3, 'W'),
+            (4, 1, 'L'),
+        ];
+        let junctions = Junctions::new(&[room], &wires(&['W', 'L']));
+        assert_eq!(sorted(junctions[0].branches.clone()), [(2, 1)]);
+        assert_eq!(sorted(junctions[0].open_ends.clone()), [(1, 1), (2, 3)]);
+    }
+
+    #[test]
+    fn wire_through_a_shared_wall() {
+        let mut a = empty_room(3, 3, 3);
+        let mut b = empty_room(3, 3, 3);
+        b.x = 3.0;
+        // W runs along the back wall of `a`, forks at its right end and goes
+        // on through the wall into the back wall of `b`.
+        a.back = vec![(0, 1, 'W'), (1, 1, 'W'), (2, 1, 'W'), (2, 2, 'W')];
+        a.right = vec![(0, 1, 'W')];
+        b.left = vec![(2, 1, 'W')];
+        b.back = vec![(0, 1, 'W'), (1, 1, 'W'), (2, 1, 'W')];
+        let junctions = Junctions::new(&[a, b], &wires(&['W']));
+        assert_eq!(junctions[0].branches, [(Wall::Back, 2, 1)]);
+        assert_eq!(sorted(junctions[0].open_ends.clone()), [(0, 1), (2, 2)]);
+        assert!(junctions[1].branches.is_empty());
+        assert_eq!(sorted(junctions[1].open_ends.clone()), [(2, 1)]);
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{CELL_SIZE, Metadata, Object, RoomShell, Rooms, ui};
+use bevy::{prelude::*, transform::TransformSystems, ui::UiSystems};
+use std::collections::HashMap;
+
+/// Shows the room names and the characters of the objects (or their `label`)
+/// in the scene. Labels fade out with the distance to the camera. `L` switches
+/// between room labels, all labels and no labels.
+pub struct LabelPlugin;
+
+impl Plugin for LabelPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LabelMode::Rooms)
+            .add_systems(PostStartup, spawn_labels)
+            .add_systems(Update, switch_labels.run_if(not(ui::typing)))
+            .add_systems(
+                PostUpdate,
+                move_labels
+                    .after(TransformSystems::Propagate)
+                    .before(UiSystems::Layout),
+            );
+    }
+}
+
+#[derive(Resource, Clone, Copy, PartialEq)]
+pub enum LabelMode {
+    Rooms,
+    All,
+    None,
+}
+
+/// Distances from the camera where room labels start and end fading.
+const ROOM_FADE: (f32, f32) = (4.0, 8.0);
+/// Distances from the camera where object labels start and end fading.
+const OBJECT_FADE: (f32, f32) = (1.0, 2.0);
+
+/// A text following a point in the scene.
+#[derive(Component)]
+struct SceneLabel {
+    anchor: Entity,
+    /// Position relative to the anchor.
+    offset: Vec3,
+    is_room: bool,
+}
+
+fn spawn_labels(
+    mut commands: Commands,
+    rooms: Res<Rooms>,
+    metadata: Res<Metadata>,
+    shells: Query<(Entity, &RoomShell)>,
+    objects: Query<(&Object, &Transform, &ChildOf)>,
+) {
+    let label = |text: String, anchor: Entity, offset: Vec3, is_room: bool| {
+        (
+            SceneLabel {
+                anchor,
+                offset,
+                is_room,
+            },
+            Text::new(text),
+            TextFont::from_font_size(if is_room { 18.0 } else { 14.0 }),
+            TextColor(Color::WHITE),
+            TextShadow::default(),
+            Node {
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            UiTransform::from_translation(Val2::percent(-50.0, -50.0)),
+            Visibility::Hidden,
+            Pickable::IGNORE,
+        )
+    };
+    for (entity, shell) in &shells {
+        let room = &rooms.0[shell.0];
+        commands.spawn(label(
+            room.name.clone(),
+            entity,
+            Vec3::Y * room.height * CELL_SIZE / 2.0,
+            true,
+        ));
+    }
+
+    // One label per character and room, in the middle of its cells.
+    let mut regions: HashMap<(Entity, char), Vec<Vec3>> = HashMap::new();
+    for (object, transform, parent) in &objects {
+        regions
+            .entry((parent.parent(), object.0))
+            .or_default()
+            .push(transform.translation);
+    }
+    for ((anchor, ch), cells) in regions {
+        let text = metadata
+            .get(ch, "label")
+            .map(String::from)
+            .unwrap_or(ch.to_string());
+        let center = cells.iter().sum::<Vec3>() / cells.len() as f32;
+        commands.spawn(label(text, anchor, center, false));
+    }
+}
+
+fn switch_labels(keys: Res<ButtonInput<KeyCode>>, mut mode: ResMut<LabelMode>) {
+    if keys.just_pressed(KeyCode::KeyL) {
+        *mode = match *mode {
+            LabelMode::Rooms => LabelMode::All,
+            LabelMode::All => LabelMode::None,
+            LabelMode::None => LabelMode::Rooms,
+        };
+    }
+}
+
+fn move_labels(
+    mode: Res<LabelMode>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    anchors: Query<(&GlobalTransform, &InheritedVisibility)>,
+    mut labels: Query<(&SceneLabel, &mut Node, &mut TextColor, &mut Visibility)>,
+) {
+    let Ok((camera, camera_transform)) = cameras.single() else {
+        return;
+    };
+    for (label, mut node, mut color, mut visibility) in &mut labels {
+        let shown = match *mode {
+            LabelMode::Rooms => label.is_room,
+            LabelMode::All => true,
+            LabelMode::None => false,
+        };
+        let Ok((anchor, anchor_visibility)) = anchors.get(label.anchor) else {
+            continue;
+        };
+        let position = anchor.transform_point(label.offset);
+        let (start, end) = if label.is_room {
+            ROOM_FADE
+        } else {
+            OBJECT_FADE
+        };
+        let distance = camera_transform.translation().distance(position);
+        let alpha = 1.0 - ((distance - start) / (end - start)).clamp(0.0, 1.0);
+        let viewport = camera
+            .world_to_viewport(camera_transform, position)
+            .ok()
+            .filter(|_| shown && alpha > 0.0 && anchor_visibility.get());
+        let Some(viewport) = viewport else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+        node.left = Val::Px(viewport.x);
+        node.top = Val::Px(viewport.y);
+        color.0.set_alpha(alpha);
+    }
+}
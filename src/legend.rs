@@ -0,0 +1,206 @@
+use crate::{
+    Cell, Descriptions, Object, Rooms, SurfaceGraph, Wall, count_cells, isolate::Isolate,
+    levels::Levels, palette::Colors, plan::PlanView, ui,
+};
+use bevy::{prelude::*, ui::Checked};
+use std::collections::{HashMap, HashSet};
+
+/// Lists every character with its colour, number of cells and regions, the
+/// rooms it appears in and its description. The checkboxes hide or show the
+/// objects of a character.
+pub struct LegendPlugin;
+
+impl Plugin for LegendPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HiddenCharacters::default())
+            .add_systems(PostStartup, spawn_legend)
+            .add_systems(
+                Update,
+                (collapse_legend, read_checkboxes, apply_object_visibility).chain(),
+            );
+    }
+}
+
+/// Characters whose objects are hidden.
+#[derive(Resource, Default)]
+pub struct HiddenCharacters(pub HashSet<char>);
+
+#[derive(Component)]
+struct LegendHeader;
+
+#[derive(Component)]
+struct LegendBody;
+
+#[derive(Component)]
+struct CharacterToggle(char);
+
+/// Number of separate groups of touching cells of every character. Cells on a
+/// wall shared by two rooms connect the rooms.
+fn count_regions(rooms: &Rooms) -> HashMap<char, usize> {
+    let graph = SurfaceGraph::new(&rooms.0);
+    let characters: HashSet<char> = rooms
+        .0
+        .iter()
+        .flat_map(|room| room.surface_cells())
+        .map(|(_, ch)| ch)
+        .collect();
+    characters
+        .into_iter()
+        .map(|ch| (ch, graph.regions(ch).len()))
+        .collect()
+}
+
+fn spawn_legend(
+    mut commands: Commands,
+    rooms: Res<Rooms>,
+    descriptions: Res<Descriptions>,
+    colors: Res<Colors>,
+) {
+    let cells = count_cells(&rooms.0);
+    let regions = count_regions(&rooms);
+    let mut characters: Vec<char> = cells.keys().copied().collect();
+    characters.sort();
+
+    let legend = commands
+        .spawn(ui::panel(Node {
+            left: Val::Px(10.0),
+            top: Val::Px(40.0),
+            max_width: Val::Percent(30.0),
+            max_height: Val::Percent(60.0),
+            ..default()
+        }))
+        .id();
+    commands.spawn((LegendHeader, Button, Text::new("Legend ▸"), ChildOf(legend)));
+    let body = commands
+        .spawn((
+            LegendBody,
+            Node {
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(6.0),
+                overflow: Overflow::scroll_y(),
+                display: Display::None,
+                ..default()
+            },
+            ChildOf(legend),
+        ))
+        .id();
+    for ch in characters {
+        let room_names: Vec<&str> = rooms
+            .0
+            .iter()
+            .filter(|room| {
+                room.walls()
+                    .iter()
+                    .any(|(_, objects)| objects.iter().any(|obj| obj.2 == ch))
+            })
+            .map(|room| room.name.as_str())
+            .collect();
+        let description = descriptions
+            .0
+            .get(&ch)
+            .and_then(|description| description.lines().next())
+            .unwrap_or("No description available.");
+        commands.spawn((
+            Node {
+                column_gap: Val::Px(6.0),
+                align_items: AlignItems::Start,
+                ..default()
+            },
+            ChildOf(body),
+            children![
+                (
+                    CharacterToggle(ch),
+                    ui::checkbox(Color::Hsla(colors.get(ch))),
+                    Checked,
+                ),
+                Text::new(format!(
+                    "{ch}: {} cells, {} regions in {}\n{description}",
+                    cells[&ch],
+                    regions.get(&ch).copied().unwrap_or(0),
+                    room_names.join(", "),
+                )),
+            ],
+        ));
+    }
+}
+
+fn collapse_legend(
+    headers: Query<&Interaction, (With<LegendHeader>, Changed<Interaction>)>,
+    mut header_texts: Query<&mut Text, With<LegendHeader>>,
+    mut bodies: Query<&mut Node, With<LegendBody>>,
+) {
+    if !headers
+        .iter()
+        .any(|interaction| *interaction == Interaction::Pressed)
+    {
+        return;
+    }
+    for mut body in &mut bodies {
+        let open = body.display == Display::None;
+        body.display = if open { Display::Flex } else { Display::None };
+        for mut text in &mut header_texts {
+            text.0 = if open { "Legend ▾" } else { "Legend ▸" }.to_string();
+        }
+    }
+}
+
+fn read_checkboxes(
+    mut hidden: ResMut<HiddenCharacters>,
+    toggles: Query<(&CharacterToggle, Has<Checked>)>,
+) {
+    for (toggle, checked) in &toggles {
+        if checked && hidden.0.contains(&toggle.0) {
+            hidden.0.remove(&toggle.0);
+        } else if !checked && !hidden.0.contains(&toggle.0) {
+            hidden.0.insert(toggle.0);
+        }
+    }
+}
+
+/// Hides the objects of hidden characters and the ceilings in the plan view. In
+/// isolate mode only the isolated objects are shown, even inside hidden rooms.
+fn apply_object_visibility(
+    hidden: Res<HiddenCharacters>,
+    plan: Res<PlanView>,
+    isolate: Res<Isolate>,
+    levels: Res<Levels>,
+    mut objects: Query<(&Object, &Cell, &mut Visibility)>,
+) {
+    if !hidden.is_changed() && !plan.is_changed() && !isolate.is_changed() && !levels.is_changed() {
+        return;
+    }
+    for (object, cell, mut visibility) in &mut objects {
+        let hide = hidden.0.contains(&object.0) || plan.0 && cell.wall == Wall::Top;
+        visibility.set_if_neq(if hide {
+            Visibility::Hidden
+        } else if !isolate.is_active() {
+            Visibility::Inherited
+        } else if isolate.characters.contains(&object.0) && levels.is_shown(cell.room) {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::empty_room;
+
+    #[test]
+    fn regions_through_shared_walls() {
+        let mut a = empty_room(3, 3, 3);
+        let mut b = empty_room(3, 3, 3);
+        b.x = 3.0;
+        // W runs from the right wall of `a` into the left wall of `b`, which
+        // are the same wall, and A shares an edge cell with W.
+        a.right = vec![(0, 1, 'W'), (1, 1, 'W')];
+        a.back = vec![(2, 1, 'A')];
+        b.left = vec![(1, 1, 'W'), (2, 1, 'W')];
+        b.floor = vec![(1, 1, 'W')];
+        let regions = count_regions(&Rooms(vec![a, b]));
+        assert_eq!(regions[&'W'], 2);
+        assert_eq!(regions[&'A'], 1);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{
+    CELL_SIZE, Room, RoomShell, Rooms, Settings, camera::Flight, explode::Explode,
+    isolate::Isolate, ui,
+};
+use bevy::{
+    prelude::*,
+    ui_widgets::{SliderRange, SliderValue},
+};
+
+/// Groups the rooms into levels. `PageUp` and `PageDown` step through the
+/// levels to show one at a time, and the slider pulls all levels apart
+/// vertically so upper floors do not hide the wiring below. The rooms are
+/// moved here for the exploded view as well.
+pub struct LevelPlugin;
+
+impl Plugin for LevelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, (insert_levels, spawn_level_panel).chain())
+            .add_systems(
+                Update,
+                (
+                    select_level.run_if(not(ui::typing)),
+                    read_gap_slider,
+                    show_level,
+                    move_rooms,
+                )
+                    .chain(),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct Levels {
+    /// Level names from bottom to top.
+    pub names: Vec<String>,
+    /// Level of every room.
+    pub of_room: Vec<usize>,
+    /// The only level shown, or all levels if `None`.
+    pub shown: Option<usize>,
+    /// Vertical space added between two levels.
+    pub gap: f32,
+}
+
+impl Levels {
+    pub fn new(rooms: &[Room], cell_size: f32) -> Self {
+        let elevation = |room: &Room| room.y - room.height / 2.0;
+        // Rooms without a level share it with the rooms on the same floor height.
+        let room_levels: Vec<String> = rooms
+            .iter()
+            .map(|room| {
+                room.level.clone().unwrap_or_else(|| {
+                    rooms
+                        .iter()
+                        .find(|other| {
+                            other.level.is_some()
+                                && (elevation(other) - elevation(room)).abs() < 0.5
+                        })
+                        .and_then(|other| other.level.clone())
+                        .unwrap_or_else(|| format!("Floor at {:.2} m", elevation(room) * cell_size))
+                })
+            })
+            .collect();
+
+        let mut levels: Vec<(f32, String)> = Vec::new();
+        for (room, name) in rooms.iter().zip(&room_levels) {
+            match levels.iter_mut().find(|(_, level)| level == name) {
+                Some((lowest, _)) => *lowest = lowest.min(elevation(room)),
+                None => levels.push((elevation(room), name.clone())),
+            }
+        }
+        levels.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let names: Vec<String> = levels.into_iter().map(|(_, name)| name).collect();
+        let of_room = room_levels
+            .iter()
+            .map(|name| names.iter().position(|level| level == name).unwrap())
+            .collect();
+        Levels {
+            names,
+            of_room,
+            shown: None,
+            gap: 0.0,
+        }
+    }
+
+    pub fn is_shown(&self, room: usize) -> bool {
+        self.shown.is_none_or(|shown| shown == self.of_room[room])
+    }
+
+    /// Bounds of the shown rooms once they have moved to their place.
+    pub fn bounds(&self, rooms: &[Room], explode: &Explode) -> Option<(Vec3, Vec3)> {
+        (0..rooms.len())
+            .filter(|&index| self.is_shown(index))
+            .map(|index| {
+                let room = &rooms[index];
+                let center = room_translation(room, self.of_room[index], self.gap, explode);
+                let half_size = Vec3::new(room.width, room.height, room.depth) * CELL_SIZE / 2.0;
+                (center - half_size, center + half_size)
+            })
+            .reduce(|(min, max), (other_min, other_max)| (min.min(other_min), max.max(other_max)))
+    }
+}
+
+#[derive(Component)]
+struct LevelText;
+
+#[derive(Component)]
+struct GapSlider;
+
+fn insert_levels(mut commands: Commands, rooms: Res<Rooms>, settings: Res<Settings>) {
+    commands.insert_resource(Levels::new(&rooms.0, settings.cell_size()));
+}
+
+fn spawn_level_panel(
+    mut commands: Commands,
+    levels: Res<Levels>,
+    tools: Single<Entity, With<ui::Tools>>,
+) {
+    if levels.names.len() < 2 {
+        return;
+    }
+    commands
+        .spawn(ui::tool_panel(*tools))
+        .with_children(|parent| {
+            parent.spawn((LevelText, Text::new("Level: all (PageUp/PageDown)")));
+            parent.spawn(Text::new("Level gap"));
+            parent.spawn((GapSlider, ui::slider(0.0, SliderRange::new(0.0, 1.0))));
+        });
+}
+
+fn select_level(keys: Res<ButtonInput<KeyCode>>, mut levels: ResMut<Levels>) {
+    let count = levels.names.len();
+    // Steps through all levels, then the bottom level up to the top level.
+    if keys.just_pressed(KeyCode::PageUp) {
+        levels.shown = match levels.shown {
+            None => Some(0),
+            Some(level) if level + 1 < count => Some(level + 1),
+            Some(_) => None,
+        };
+    } else if keys.just_pressed(KeyCode::PageDown) {
+        levels.shown = match levels.shown {
+            None => count.checked_sub(1),
+            Some(0) => None,
+            Some(level) => Some(level - 1),
+        };
+    }
+}
+
+fn read_gap_slider(
+    mut levels: ResMut<Levels>,
+    sliders: Query<&SliderValue, (With<GapSlider>, Changed<SliderValue>)>,
+) {
+    for value in &sliders {
+        levels.gap = value.0;
+    }
+}
+
+/// Hides the rooms of the other levels, and all rooms in isolate mode, and
+/// frames the shown rooms.
+#[allow(clippy::too_many_arguments)]
+fn show_level(
+    mut commands: Commands,
+    levels: Res<Levels>,
+    isolate: Res<Isolate>,
+    rooms: Res<Rooms>,
+    explode: Res<Explode>,
+    mut shells: Query<(&RoomShell, &mut Visibility)>,
+    cameras: Query<(Entity, &Transform), With<Camera3d>>,
+    mut texts: Query<&mut Text, With<LevelText>>,
+    mut previous: Local<Option<usize>>,
+) {
+    if levels.is_changed() || isolate.is_changed() {
+        for (shell, mut visibility) in &mut shells {
+            visibility.set_if_neq(if levels.is_shown(shell.0) && !isolate.is_active() {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            });
+        }
+    }
+    if levels.shown == *previous {
+        return;
+    }
+    *previous = levels.shown;
+    for mut text in &mut texts {
+        text.0 = format!(
+            "Level: {} (PageUp/PageDown)",
+            levels
+                .shown
+                .map(|level| levels.names[level].as_str())
+                .unwrap_or("all")
+        );
+    }
+    let Some(bounds) = levels.bounds(&rooms.0, &explode) else {
+        return;
+    };
+    for (entity, transform) in &cameras {
+        commands
+            .entity(entity)
+            .insert(Flight::frame(transform, bounds));
+    }
+}
+
+fn room_translation(room: &Room, level: usize, gap: f32, explode: &Explode) -> Vec3 {
+    (Vec3::new(room.x, room.y, room.z) + explode.offset(room)) * CELL_SIZE
+        + Vec3::Y * gap * level as f32
+}
+
+fn move_rooms(
+    time: Res<Time>,
+    levels: Res<Levels>,
+    rooms: Res<Rooms>,
+    explode: Res<Explode>,
+    mut shells: Query<(&RoomShell, &mut Transform)>,
+) {
+    for (shell, mut transform) in &mut shells {
+        let target = room_translation(
+            &rooms.0[shell.0],
+            levels.of_room[shell.0],
+            levels.gap,
+            &explode,
+        );
+        if transform.translation != target {
+            transform
+                .translation
+                .smooth_nudge(&target, 8.0, time.delta_secs());
+            if transform.translation.distance(target) < 0.0001 {
+                transform.translation = target;
+            }
+        }
+    }
+}
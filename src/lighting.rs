@@ -0,0 +1,197 @@
+use crate::{Object, RoomShell, Settings, circuit::LampLight, sun::Sun, surfaces::Surface, ui};
+use bevy::{camera::Exposure, light::NotShadowCaster, prelude::*, ui::Checked};
+
+/// A lighting mode (`N`) with little ambient light and an indoor exposure, so
+/// the light of the switched-on lamps can be judged. Shadows are optional, as
+/// they are expensive: `shadows: yes` in the settings or the checkbox. They are
+/// always on while the sun shines.
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Lighting::default())
+            .add_systems(PostStartup, spawn_lighting_panel)
+            .add_systems(
+                Update,
+                (
+                    toggle_lighting.run_if(not(ui::typing)),
+                    read_shadow_checkbox,
+                    apply_lighting,
+                    apply_shadows,
+                )
+                    .chain(),
+            );
+    }
+}
+
+/// Brightness of the ambient light in the lighting mode.
+const NIGHT_BRIGHTNESS: f32 = 10.0;
+
+#[derive(Resource, Default)]
+pub struct Lighting {
+    pub enabled: bool,
+    pub shadows: bool,
+}
+
+/// Marks the light brightening the whole scene outside of the lighting mode.
+#[derive(Component)]
+pub struct FillLight;
+
+#[derive(Component)]
+struct LightingText;
+
+#[derive(Component)]
+struct ShadowToggle;
+
+/// Colour of a light source with a colour temperature in kelvin, after the
+/// approximation by Tanner Helland.
+pub fn color_temperature(kelvin: f32) -> Color {
+    let t = kelvin.clamp(1000.0, 40000.0) / 100.0;
+    let red = if t <= 66.0 {
+        255.0
+    } else {
+        329.7 * (t - 60.0).powf(-0.1332)
+    };
+    let green = if t <= 66.0 {
+        99.47 * t.ln() - 161.12
+    } else {
+        288.12 * (t - 60.0).powf(-0.0755)
+    };
+    let blue = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.52 * (t - 10.0).ln() - 305.04
+    };
+    Color::srgb_u8(
+        red.clamp(0.0, 255.0) as u8,
+        green.clamp(0.0, 255.0) as u8,
+        blue.clamp(0.0, 255.0) as u8,
+    )
+}
+
+fn spawn_lighting_panel(
+    mut commands: Commands,
+    tools: Single<Entity, With<ui::Tools>>,
+    settings: Res<Settings>,
+    mut lighting: ResMut<Lighting>,
+) {
+    lighting.shadows = settings
+        .0
+        .get("shadows")
+        .is_some_and(|value| value == "yes");
+    let panel = commands
+        .spawn(ui::tool_panel(*tools))
+        .with_child((LightingText, Text::new("Lighting (N): off")))
+        .id();
+    let toggle = commands
+        .spawn((ShadowToggle, ui::checkbox(Color::WHITE)))
+        .id();
+    if lighting.shadows {
+        commands.entity(toggle).insert(Checked);
+    }
+    commands
+        .spawn((
+            Node {
+                column_gap: Val::Px(6.0),
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            ChildOf(panel),
+        ))
+        .add_child(toggle)
+        .with_child(Text::new("Shadows"));
+}
+
+fn toggle_lighting(keys: Res<ButtonInput<KeyCode>>, mut lighting: ResMut<Lighting>) {
+    if keys.just_pressed(KeyCode::KeyN) {
+        lighting.enabled = !lighting.enabled;
+    }
+}
+
+fn read_shadow_checkbox(
+    mut lighting: ResMut<Lighting>,
+    toggles: Query<Has<Checked>, With<ShadowToggle>>,
+) {
+    for checked in &toggles {
+        if checked != lighting.shadows {
+            lighting.shadows = checked;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_lighting(
+    mut commands: Commands,
+    lighting: Res<Lighting>,
+    sun: Res<Sun>,
+    mut ambient: ResMut<GlobalAmbientLight>,
+    mut daylight_brightness: Local<Option<f32>>,
+    mut fill_lights: Query<&mut Visibility, With<FillLight>>,
+    cameras: Query<Entity, With<Camera3d>>,
+    mut texts: Query<&mut Text, With<LightingText>>,
+) {
+    if !lighting.is_changed() && !sun.is_changed() {
+        return;
+    }
+    let daylight_brightness = *daylight_brightness.get_or_insert(ambient.brightness);
+    ambient.brightness = if lighting.enabled {
+        NIGHT_BRIGHTNESS
+    } else {
+        daylight_brightness
+    };
+    // The sun replaces the light from below.
+    for mut visibility in &mut fill_lights {
+        *visibility = if lighting.enabled || sun.enabled {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+    }
+    for camera in &cameras {
+        commands.entity(camera).insert(if lighting.enabled {
+            Exposure::INDOOR
+        } else {
+            Exposure::default()
+        });
+    }
+    for mut text in &mut texts {
+        text.0 = format!(
+            "Lighting (N): {}",
+            if lighting.enabled { "on" } else { "off" }
+        );
+    }
+}
+
+/// Lets the lamps and the sun cast shadows of the walls and objects.
+#[allow(clippy::type_complexity)]
+fn apply_shadows(
+    mut commands: Commands,
+    lighting: Res<Lighting>,
+    sun: Res<Sun>,
+    mut point_lights: Query<&mut PointLight, With<LampLight>>,
+    mut spot_lights: Query<&mut SpotLight, With<LampLight>>,
+    casters: Query<Entity, Or<(With<RoomShell>, With<Surface>, With<Object>)>>,
+) {
+    for mut light in &mut point_lights {
+        if light.shadow_maps_enabled != lighting.shadows {
+            light.shadow_maps_enabled = lighting.shadows;
+        }
+    }
+    for mut light in &mut spot_lights {
+        if light.shadow_maps_enabled != lighting.shadows {
+            light.shadow_maps_enabled = lighting.shadows;
+        }
+    }
+    if !lighting.is_changed() && !sun.is_changed() {
+        return;
+    }
+    for entity in &casters {
+        if lighting.shadows || sun.enabled {
+            commands.entity(entity).remove::<NotShadowCaster>();
+        } else {
+            commands.entity(entity).insert(NotShadowCaster);
+        }
+    }
+}
@@ -4,12 +4,110 @@ use bevy::{
 };
 use std::collections::HashMap;
 
+mod camera;
+mod circuit;
+mod cursor;
+mod cutaway;
+mod explode;
+mod grid;
+mod inspector;
+mod isolate;
+mod junctions;
+mod labels;
+mod legend;
+mod levels;
+mod lighting;
+mod measure;
+mod network;
+mod openings;
+mod palette;
+mod plan;
+mod plumbing;
+mod search;
+mod shapes;
+mod sun;
+mod surfaces;
+mod ui;
+mod voltage;
+mod walk;
+
+/// Size of one character of the ascii sketches in the scene.
+const CELL_SIZE: f32 = 1.0 / 18.0;
+
+/// Offsets to the neighbours of a point on the surface of a room.
+const NEIGHBOURS: [IVec3; 6] = [
+    IVec3::X,
+    IVec3::NEG_X,
+    IVec3::Y,
+    IVec3::NEG_Y,
+    IVec3::Z,
+    IVec3::NEG_Z,
+];
+
 #[derive(Component)]
 struct Object(char);
 
+/// Where an object was drawn: the room index, the wall and the column and row
+/// within the wall section of the net.
+#[derive(Component, Clone, Copy)]
+struct Cell {
+    room: usize,
+    wall: Wall,
+    x: usize,
+    y: usize,
+}
+
 #[derive(Resource)]
 struct Descriptions(HashMap<char, String>);
 
+/// Marks the text showing the description of the hovered object.
+#[derive(Component)]
+struct HoverText;
+
+/// Key-value pairs written as `key: value` lines in the section of a character,
+/// e.g. `type: switch`.
+#[derive(Resource)]
+struct Metadata(HashMap<char, HashMap<String, String>>);
+
+impl Metadata {
+    fn get(&self, ch: char, key: &str) -> Option<&str> {
+        self.0.get(&ch)?.get(key).map(String::as_str)
+    }
+
+    fn is_type(&self, ch: char, object_type: &str) -> bool {
+        self.get(ch, "type") == Some(object_type)
+    }
+
+    fn number(&self, ch: char, key: &str) -> Option<f32> {
+        self.get(ch, key)?.parse().ok()
+    }
+}
+
+/// Key-value pairs written as `key: value` lines before the first heading.
+#[derive(Resource)]
+struct Settings(HashMap<String, String>);
+
+impl Settings {
+    fn number(&self, key: &str, default: f32) -> f32 {
+        self.0
+            .get(key)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default)
+    }
+
+    /// Real length of one character of the ascii sketches in metres.
+    fn cell_size(&self) -> f32 {
+        self.number("cell-size", 0.25)
+    }
+}
+
+/// Marks the mesh of a room with its index in [`Rooms`].
+#[derive(Component)]
+struct RoomShell(usize);
+
+#[derive(Resource)]
+struct Rooms(Vec<Room>);
+
 #[derive(Default, Debug)]
 struct LocationsOfChar {
     top: HashMap<usize, Vec<Position>>,
@@ -78,14 +176,49 @@ fn get_input() -> String {
         .value()
 }
 
+/// Command line options, given like `--port-map=ports.csv`.
+const OPTIONS: [&str; 2] = ["palette", "port-map"];
+
+/// Splits the command line arguments into files and options.
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+fn parse_args(
+    args: impl IntoIterator<Item = String>,
+) -> Result<(Vec<String>, HashMap<String, String>), String> {
+    let mut files = Vec::new();
+    let mut options = HashMap::new();
+    for arg in args {
+        let Some(option) = arg.strip_prefix("--") else {
+            files.push(arg);
+            continue;
+        };
+        let (name, value) = option.split_once('=').unwrap_or((option, ""));
+        if !OPTIONS.contains(&name) {
+            return Err(format!("Unknown option '--{name}'."));
+        }
+        if value.is_empty() {
+            return Err(format!(
+                "Option '--{name}' needs a value like '--{name}=...'."
+            ));
+        }
+        options.insert(name.to_string(), value.to_string());
+    }
+    Ok((files, options))
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn get_input() -> String {
-    let args: Vec<String> = std::env::args().collect();
-    if args.len() != 2 {
+    let files = match parse_args(std::env::args().skip(1)) {
+        Ok((files, _)) => files,
+        Err(error) => {
+            eprintln!("Error: {error}");
+            std::process::exit(1);
+        }
+    };
+    if files.len() != 1 {
         eprintln!("Error: Exactly one markdown file (.md) must be provided.");
         std::process::exit(1);
     }
-    let path = std::path::Path::new(&args[1]);
+    let path = std::path::Path::new(&files[0]);
     if path.extension().map(|ext| ext != "md").unwrap_or(true) {
         eprintln!("Error: Failed to read file '{}'", path.display());
         std::process::exit(1);
@@ -93,6 +226,17 @@ fn get_input() -> String {
     std::fs::read_to_string(path).expect("Error reading file.")
 }
 
+/// Returns the value of a command line option like `--port-map=ports.csv`.
+#[cfg(not(target_arch = "wasm32"))]
+fn get_option(name: &str) -> Option<String> {
+    parse_args(std::env::args().skip(1)).ok()?.1.remove(name)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn get_option(_name: &str) -> Option<String> {
+    None
+}
+
 pub fn get_letters_in_ascii_grid(
     image: Vec<&str>,
     x: usize,
@@ -115,6 +259,78 @@ pub fn get_letters_in_ascii_grid(
         .collect()
 }
 
+/// Splits the markdown text at the headings, a `#` at the start of a line, so
+/// a `#` within a line like `color: #E69F00` stays part of the section. The
+/// first part is the text before the first heading.
+fn split_sections(text: &str) -> Vec<&str> {
+    let mut sections = Vec::new();
+    let mut start = 0;
+    for (index, _) in text.match_indices('#') {
+        let line_start = text[..index].rfind('\n').map_or(0, |i| i + 1);
+        if index >= start && text[line_start..index].trim().is_empty() {
+            sections.push(&text[start..index]);
+            start = index + text[index..].len() - text[index..].trim_start_matches('#').len();
+        }
+    }
+    sections.push(&text[start..]);
+    sections
+}
+
+/// Keys read as metadata from `key: value` lines in the settings and in the
+/// sections of rooms and characters. Walls take `{wall}-material` as well.
+const METADATA_KEYS: [&str; 27] = [
+    "angle",
+    "cell-size",
+    "color",
+    "cross-section",
+    "date",
+    "diameter",
+    "flow",
+    "glass",
+    "kelvin",
+    "label",
+    "latitude",
+    "level",
+    "light",
+    "load",
+    "lumen",
+    "material",
+    "max-voltage-drop",
+    "min-slope",
+    "model",
+    "north",
+    "palette",
+    "ports",
+    "shadows",
+    "shape",
+    "type",
+    "voltage",
+    "walls-material",
+];
+
+fn is_metadata_key(key: &str) -> bool {
+    METADATA_KEYS.contains(&key)
+        || key
+            .strip_suffix("-material")
+            .is_some_and(|name| Wall::ALL.iter().any(|wall| wall.to_string() == name))
+}
+
+/// Splits the `key: value` lines with a known key off a description section.
+/// Other lines like `note: check with client` stay part of the description.
+fn split_metadata(content: &str) -> (HashMap<String, String>, String) {
+    let mut metadata = HashMap::new();
+    let mut description = Vec::new();
+    for line in content.lines() {
+        match line.trim().split_once(':') {
+            Some((key, value)) if is_metadata_key(key) => {
+                metadata.insert(key.to_string(), value.trim().to_string());
+            }
+            _ => description.push(line),
+        }
+    }
+    (metadata, description.join("\n").trim().to_string())
+}
+
 fn main() {
     App::new()
         .add_plugins((
@@ -128,13 +344,56 @@ fn main() {
             MeshPickingPlugin,
             WindowResizePlugin,
             FreeCameraPlugin,
+            ui::WidgetPlugin,
+            camera::CameraPlugin,
+        ))
+        // Analysis of the building.
+        .add_plugins((
+            circuit::CircuitPlugin,
+            junctions::JunctionPlugin,
+            voltage::VoltagePlugin,
+            network::NetworkPlugin,
+            plumbing::PlumbingPlugin,
+        ))
+        // Tools of the viewer.
+        .add_plugins((
+            inspector::InspectorPlugin,
+            search::SearchPlugin,
+            cutaway::CutawayPlugin,
+            levels::LevelPlugin,
+            explode::ExplodePlugin,
+            labels::LabelPlugin,
+            plan::PlanPlugin,
+            measure::MeasurePlugin,
+            cursor::CursorPlugin,
+            legend::LegendPlugin,
+            walk::WalkPlugin,
+            isolate::IsolatePlugin,
+            grid::GridPlugin,
+        ))
+        // Appearance of the building.
+        .add_plugins((
+            shapes::ShapePlugin,
+            surfaces::SurfacePlugin,
+            lighting::LightingPlugin,
+            sun::SunPlugin,
         ))
         .add_systems(Startup, setup)
         .insert_resource(Descriptions(HashMap::new()))
+        .insert_resource(Metadata(HashMap::new()))
+        .insert_resource(Settings(HashMap::new()))
         .run();
 }
 
 struct Room {
+    name: String,
+    /// Given as `level: ...` in the room section or as the heading prefix
+    /// before `:`, for example `# Ground floor: Kitchen`.
+    level: Option<String>,
+    /// Line of the first row of the net in the markdown file.
+    source_line: usize,
+    /// Key-value pairs written as `key: value` lines in the room section.
+    metadata: HashMap<String, String>,
     x: f32,
     y: f32,
     z: f32,
@@ -149,6 +408,279 @@ struct Room {
     back: Vec<(usize, usize, char)>,
 }
 
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+enum Wall {
+    Top,
+    Back,
+    Right,
+    Front,
+    Left,
+    Floor,
+}
+
+impl Wall {
+    /// Column, row, width and height of the wall section in the net of a room.
+    fn net_section(
+        self,
+        width: usize,
+        height: usize,
+        depth: usize,
+    ) -> (usize, usize, usize, usize) {
+        match self {
+            Wall::Top => (0, 0, width, depth),
+            Wall::Back => (0, depth, width, height),
+            Wall::Right => (width, depth, depth, height),
+            Wall::Front => (width + depth, depth, width, height),
+            Wall::Left => (2 * width + depth, depth, depth, height),
+            Wall::Floor => (0, depth + height, width, depth),
+        }
+    }
+
+    const ALL: [Wall; 6] = [
+        Wall::Top,
+        Wall::Back,
+        Wall::Right,
+        Wall::Front,
+        Wall::Left,
+        Wall::Floor,
+    ];
+
+    fn facing(normal: IVec3) -> Option<Wall> {
+        Wall::ALL.into_iter().find(|wall| wall.normal() == normal)
+    }
+
+    fn normal(self) -> IVec3 {
+        match self {
+            Wall::Top => IVec3::Y,
+            Wall::Back => IVec3::NEG_Z,
+            Wall::Right => IVec3::X,
+            Wall::Front => IVec3::Z,
+            Wall::Left => IVec3::NEG_X,
+            Wall::Floor => IVec3::NEG_Y,
+        }
+    }
+}
+
+impl std::fmt::Display for Wall {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            Wall::Top => "top",
+            Wall::Back => "back",
+            Wall::Right => "right",
+            Wall::Front => "front",
+            Wall::Left => "left",
+            Wall::Floor => "floor",
+        };
+        write!(f, "{name}")
+    }
+}
+
+impl Room {
+    #[allow(clippy::type_complexity)]
+    fn walls(&self) -> [(Wall, &Vec<(usize, usize, char)>); 6] {
+        [
+            (Wall::Top, &self.top),
+            (Wall::Back, &self.back),
+            (Wall::Right, &self.right),
+            (Wall::Front, &self.front),
+            (Wall::Left, &self.left),
+            (Wall::Floor, &self.floor),
+        ]
+    }
+
+    /// Returns the point on the surface of the room for a cell of a wall.
+    /// Cells on the edge between two walls are part of both wall sections of
+    /// the net and end up on the same point.
+    fn surface_position(&self, wall: Wall, x: usize, y: usize) -> IVec3 {
+        let (w, h, d) = (self.width as i32, self.height as i32, self.depth as i32);
+        let (x, y) = (x as i32, y as i32);
+        match wall {
+            Wall::Top => IVec3::new(x, h - 1, d - 1 - y),
+            Wall::Back => IVec3::new(x, h - 1 - y, 0),
+            Wall::Right => IVec3::new(w - 1, h - 1 - y, x),
+            Wall::Front => IVec3::new(w - 1 - x, h - 1 - y, d - 1),
+            Wall::Left => IVec3::new(0, h - 1 - y, d - 1 - x),
+            Wall::Floor => IVec3::new(x, 0, y),
+        }
+    }
+
+    /// Returns the objects of all walls as points on the surface of the room.
+    fn surface_cells(&self) -> Vec<(IVec3, char)> {
+        self.walls()
+            .into_iter()
+            .flat_map(|(wall, objects)| {
+                objects
+                    .iter()
+                    .map(move |&(x, y, ch)| (self.surface_position(wall, x, y), ch))
+            })
+            .collect()
+    }
+
+    /// Converts a point on the surface to cells relative to the room centre.
+    fn local_position(&self, position: IVec3) -> Vec3 {
+        position.as_vec3() - (Vec3::new(self.width, self.height, self.depth) - 1.0) / 2.0
+    }
+
+    /// Converts a point on the surface to cells relative to the building.
+    fn world_position(&self, position: IVec3) -> Vec3 {
+        Vec3::new(self.x, self.y, self.z) + self.local_position(position)
+    }
+
+    /// Point on the wall a cell of the surface is drawn on, in half cells
+    /// relative to the building. The cells of two rooms on a wall they share
+    /// have the same spot.
+    fn wall_spot(&self, wall: Wall, position: IVec3) -> IVec3 {
+        let spot = self.world_position(position) + wall.normal().as_vec3() * 0.5;
+        (spot * 2.0).round().as_ivec3()
+    }
+
+    /// Returns the cell of a wall for a point on the surface, the inverse of
+    /// `surface_position`.
+    fn wall_cell(&self, wall: Wall, position: IVec3) -> (usize, usize) {
+        let (w, h, d) = (self.width as i32, self.height as i32, self.depth as i32);
+        let p = position;
+        let (x, y) = match wall {
+            Wall::Top => (p.x, d - 1 - p.z),
+            Wall::Back => (p.x, h - 1 - p.y),
+            Wall::Right => (p.z, h - 1 - p.y),
+            Wall::Front => (w - 1 - p.x, h - 1 - p.y),
+            Wall::Left => (d - 1 - p.z, h - 1 - p.y),
+            Wall::Floor => (p.x, p.z),
+        };
+        (x as usize, y as usize)
+    }
+
+    /// Returns the line and column of a cell in the markdown file.
+    fn source_position(&self, wall: Wall, x: usize, y: usize) -> (usize, usize) {
+        let (column, row, _, _) = wall.net_section(
+            self.width as usize,
+            self.height as usize,
+            self.depth as usize,
+        );
+        (self.source_line + row + y, column + x + 1)
+    }
+
+    /// Snaps a point on a wall, in cells relative to the room centre, to the
+    /// nearest point on the surface.
+    fn nearest_surface_position(&self, local: Vec3) -> IVec3 {
+        let size = Vec3::new(self.width, self.height, self.depth);
+        (local + (size - 1.0) / 2.0)
+            .round()
+            .clamp(Vec3::ZERO, size - 1.0)
+            .as_ivec3()
+    }
+}
+
+/// The characters at every point on the surface of every room, joined with the
+/// cells of the same character on the other side of a wall shared by two rooms.
+struct SurfaceGraph {
+    cells: HashMap<(usize, IVec3), Vec<char>>,
+    partners: HashMap<(usize, IVec3, char), Vec<(usize, IVec3)>>,
+}
+
+impl SurfaceGraph {
+    fn new(rooms: &[Room]) -> Self {
+        let mut cells: HashMap<(usize, IVec3), Vec<char>> = HashMap::new();
+        let mut spots: HashMap<(IVec3, char), Vec<(usize, IVec3)>> = HashMap::new();
+        for (index, room) in rooms.iter().enumerate() {
+            for (wall, objects) in room.walls() {
+                for &(x, y, ch) in objects {
+                    let position = room.surface_position(wall, x, y);
+                    let chars = cells.entry((index, position)).or_default();
+                    if !chars.contains(&ch) {
+                        chars.push(ch);
+                    }
+                    spots
+                        .entry((room.wall_spot(wall, position), ch))
+                        .or_default()
+                        .push((index, position));
+                }
+            }
+        }
+        let mut partners: HashMap<(usize, IVec3, char), Vec<(usize, IVec3)>> = HashMap::new();
+        for (&(_, ch), points) in &spots {
+            for &(room, position) in points {
+                partners
+                    .entry((room, position, ch))
+                    .or_default()
+                    .extend(points.iter().filter(|(other, _)| *other != room).copied());
+            }
+        }
+        SurfaceGraph { cells, partners }
+    }
+
+    /// Characters drawn at a point on the surface of a room.
+    fn chars(&self, room: usize, position: IVec3) -> &[char] {
+        self.cells.get(&(room, position)).map_or(&[], Vec::as_slice)
+    }
+
+    /// Cells of `ch` in other rooms on the other side of the wall.
+    fn partners(&self, room: usize, position: IVec3, ch: char) -> &[(usize, IVec3)] {
+        self.partners
+            .get(&(room, position, ch))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Groups the cells of `ch` into regions of touching cells, which may run
+    /// through shared walls. Cells and regions are sorted by room and point.
+    fn regions(&self, ch: char) -> Vec<Vec<(usize, IVec3)>> {
+        let order = |&(room, position): &(usize, IVec3)| (room, position.to_array());
+        let mut cells: Vec<(usize, IVec3)> = self
+            .cells
+            .iter()
+            .filter(|(_, chars)| chars.contains(&ch))
+            .map(|(&cell, _)| cell)
+            .collect();
+        cells.sort_by_key(order);
+        let mut visited = std::collections::HashSet::new();
+        let mut regions = Vec::new();
+        for start in cells {
+            if !visited.insert(start) {
+                continue;
+            }
+            let mut region = vec![start];
+            let mut stack = vec![start];
+            while let Some((room, position)) = stack.pop() {
+                let inside = NEIGHBOURS
+                    .iter()
+                    .map(|offset| (room, position + offset))
+                    .filter(|&(room, next)| self.chars(room, next).contains(&ch));
+                let through = self.partners(room, position, ch).iter().copied();
+                for next in inside.chain(through) {
+                    if visited.insert(next) {
+                        region.push(next);
+                        stack.push(next);
+                    }
+                }
+            }
+            region.sort_by_key(order);
+            regions.push(region);
+        }
+        regions
+    }
+}
+
+/// Counts the cells of every character in the building. Cells on a wall
+/// between two rooms are drawn in both rooms but only counted once.
+fn count_cells(rooms: &[Room]) -> HashMap<char, usize> {
+    let mut seen = std::collections::HashSet::new();
+    let mut counts = HashMap::new();
+    for room in rooms {
+        let mut seen_in_room = std::collections::HashSet::new();
+        for (wall, objects) in room.walls() {
+            for &(x, y, ch) in objects {
+                let position = room.surface_position(wall, x, y);
+                if seen_in_room.insert((position, ch))
+                    && seen.insert((ch, room.wall_spot(wall, position)))
+                {
+                    *counts.entry(ch).or_default() += 1;
+                }
+            }
+        }
+    }
+    counts
+}
+
 #[derive(Debug, PartialEq, Clone)]
 struct Position {
     x: i32,
@@ -160,6 +692,8 @@ fn setup(
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut descriptions: ResMut<Descriptions>,
+    mut metadata: ResMut<Metadata>,
+    mut settings: ResMut<Settings>,
     asset_server: Res<AssetServer>,
 ) {
     // Step 1: Read markdown and create lists ob objects.
@@ -167,18 +701,31 @@ fn setup(
     let mut object_list: HashMap<char, LocationsOfChar> = HashMap::new();
     let mut room_index = 0;
     let mut rooms: Vec<Room> = Vec::new();
-    for section in text.split('#').filter(|s| !s.trim().is_empty()) {
+    let mut sections = split_sections(&text).into_iter();
+    if !text.trim_start().starts_with('#') {
+        settings.0 = split_metadata(sections.next().unwrap_or("")).0;
+    }
+    for section in sections.filter(|s| !s.trim().is_empty()) {
         let mut lines = section.trim().lines();
         let first_line = lines.next().unwrap_or("").trim();
         let name = first_line.to_string();
         let content = lines.collect::<Vec<&str>>().join("\n").trim().to_string();
         if name.len() == 1 {
             let name = name.chars().next().unwrap();
-            let desc = content;
+            let (meta, desc) = split_metadata(&content);
+            metadata.0.insert(name, meta);
             descriptions.0.insert(name, desc);
         } else {
+            let (meta, content) = split_metadata(&content);
+            let level = meta.get("level").cloned().or_else(|| {
+                name.split_once(':')
+                    .map(|(prefix, _)| prefix.trim().to_string())
+            });
             let lines: Vec<&str> = content.trim().lines().collect();
-            let first_line = lines[0].trim();
+            let Some(first_line) = lines.first().map(|line| line.trim()) else {
+                eprintln!("Error: Room '{name}' has no net.");
+                continue;
+            };
             let width: usize = first_line.len();
             let depth: usize = 1 + lines
                 .clone()
@@ -193,15 +740,31 @@ fn setup(
                 2 * (width * depth + width * height + depth * height),
                 content.replace(['\n', '\r'], "").len()
             );
+            let letters = |wall: Wall| {
+                let (x, y, wdt, hgt) = wall.net_section(width, height, depth);
+                get_letters_in_ascii_grid(lines.clone(), x, y, wdt, hgt)
+            };
             let (top, back, right, front, left, floor) = (
-                get_letters_in_ascii_grid(lines.clone(), 0, 0, width, depth),
-                get_letters_in_ascii_grid(lines.clone(), 0, depth, width, height),
-                get_letters_in_ascii_grid(lines.clone(), width, depth, depth, height),
-                get_letters_in_ascii_grid(lines.clone(), width + depth, depth, width, height),
-                get_letters_in_ascii_grid(lines.clone(), 2 * width + depth, depth, depth, height),
-                get_letters_in_ascii_grid(lines.clone(), 0, depth + height, width, depth),
+                letters(Wall::Top),
+                letters(Wall::Back),
+                letters(Wall::Right),
+                letters(Wall::Front),
+                letters(Wall::Left),
+                letters(Wall::Floor),
             );
+            // Line of the net in the markdown file, counted from 1.
+            let offset = section.as_ptr() as usize - text.as_ptr() as usize;
+            let source_line = text[..offset].matches('\n').count()
+                + section
+                    .lines()
+                    .position(|line| line.trim() == first_line)
+                    .unwrap_or(0)
+                + 1;
             rooms.push(Room {
+                name: name.clone(),
+                level,
+                source_line,
+                metadata: meta.clone(),
                 x: 0.0,
                 y: 0.0,
                 z: 0.0,
@@ -344,22 +907,24 @@ fn setup(
         }
     }
 
+    let connections = circuit::Connections::new(&rooms);
+    commands.insert_resource(circuit::Circuit::new(&connections, &metadata));
+    commands.insert_resource(connections);
+
+    let colors = palette::Colors::new(&rooms, &metadata, &settings);
+
     // Step 3: Spawn rooms and objects.
     let texture = asset_server.load("texture.png");
-    let scaling2 = 1.0 / 18.0;
+    let scaling2 = CELL_SIZE;
     let scaling = scaling2 * 0.999;
-    for room in rooms {
+    for (index, room) in rooms.iter().enumerate() {
         commands
             .spawn((
                 Mesh3d(
                     meshes.add(
-                        Mesh::from(Cuboid::new(
-                            room.width * scaling2,
-                            room.height * scaling2,
-                            room.depth * scaling2,
-                        ))
-                        .with_generated_tangents()
-                        .unwrap(),
+                        openings::shell_mesh(room, &metadata, &Wall::ALL, None)
+                            .with_generated_tangents()
+                            .unwrap(),
                     ),
                 ),
                 MeshMaterial3d(materials.add(StandardMaterial {
@@ -372,6 +937,7 @@ fn setup(
                     fog_enabled: true,
                     ..default()
                 })),
+                RoomShell(index),
                 Pickable::IGNORE,
                 bevy::light::NotShadowCaster,
                 Transform::from_translation(Vec3::new(
@@ -382,7 +948,8 @@ fn setup(
             ))
             .with_children(|parent| {
                 for obj in &room.top {
-                    let white_matl = materials.add(Color::Hsla(char_to_color(obj.2)));
+                    let white_matl =
+                        materials.add(openings::object_material(&metadata, &colors, obj.2));
                     parent
                         .spawn((
                             Mesh3d(meshes.add(Cuboid::new(scaling, scaling * 0.2, scaling))),
@@ -393,12 +960,18 @@ fn setup(
                                 scaling * (0.0 - obj.1 as f32 + room.depth / 2.0 - 0.5),
                             )),
                             Object(obj.2),
+                            Cell {
+                                room: index,
+                                wall: Wall::Top,
+                                x: obj.0,
+                                y: obj.1,
+                            },
                             bevy::light::NotShadowCaster,
                         ))
                         .observe(update_material_on::<Pointer<Over>>(
                             materials.add(Color::Hsla(Hsla {
                                 lightness: 0.8,
-                                ..char_to_color(obj.2)
+                                ..colors.get(obj.2)
                             })),
                             obj.2,
                         ))
@@ -408,7 +981,8 @@ fn setup(
                         ));
                 }
                 for obj in &room.back {
-                    let white_matl = materials.add(Color::Hsla(char_to_color(obj.2)));
+                    let white_matl =
+                        materials.add(openings::object_material(&metadata, &colors, obj.2));
                     parent
                         .spawn((
                             Mesh3d(meshes.add(Cuboid::new(scaling, scaling, scaling * 0.2))),
@@ -419,12 +993,18 @@ fn setup(
                                 scaling * (0.0 - room.depth / 2.0 - 0.1),
                             )),
                             Object(obj.2),
+                            Cell {
+                                room: index,
+                                wall: Wall::Back,
+                                x: obj.0,
+                                y: obj.1,
+                            },
                             bevy::light::NotShadowCaster,
                         ))
                         .observe(update_material_on::<Pointer<Over>>(
                             materials.add(Color::Hsla(Hsla {
                                 lightness: 0.8,
-                                ..char_to_color(obj.2)
+                                ..colors.get(obj.2)
                             })),
                             obj.2,
                         ))
@@ -434,7 +1014,8 @@ fn setup(
                         ));
                 }
                 for obj in &room.right {
-                    let white_matl = materials.add(Color::Hsla(char_to_color(obj.2)));
+                    let white_matl =
+                        materials.add(openings::object_material(&metadata, &colors, obj.2));
                     parent
                         .spawn((
                             Mesh3d(meshes.add(Cuboid::new(scaling * 0.2, scaling, scaling))),
@@ -445,12 +1026,18 @@ fn setup(
                                 scaling * (obj.0 as f32 - room.height / 2.0 + 0.5),
                             )),
                             Object(obj.2),
+                            Cell {
+                                room: index,
+                                wall: Wall::Right,
+                                x: obj.0,
+                                y: obj.1,
+                            },
                             bevy::light::NotShadowCaster,
                         ))
                         .observe(update_material_on::<Pointer<Over>>(
                             materials.add(Color::Hsla(Hsla {
                                 lightness: 0.8,
-                                ..char_to_color(obj.2)
+                                ..colors.get(obj.2)
                             })),
                             obj.2,
                         ))
@@ -460,7 +1047,8 @@ fn setup(
                         ));
                 }
                 for obj in &room.front {
-                    let white_matl = materials.add(Color::Hsla(char_to_color(obj.2)));
+                    let white_matl =
+                        materials.add(openings::object_material(&metadata, &colors, obj.2));
                     parent
                         .spawn((
                             Mesh3d(meshes.add(Cuboid::new(scaling, scaling, scaling * 0.2))),
@@ -471,12 +1059,18 @@ fn setup(
                                 scaling * (room.depth / 2.0 + 0.1),
                             )),
                             Object(obj.2),
+                            Cell {
+                                room: index,
+                                wall: Wall::Front,
+                                x: obj.0,
+                                y: obj.1,
+                            },
                             bevy::light::NotShadowCaster,
                         ))
                         .observe(update_material_on::<Pointer<Over>>(
                             materials.add(Color::Hsla(Hsla {
                                 lightness: 0.8,
-                                ..char_to_color(obj.2)
+                                ..colors.get(obj.2)
                             })),
                             obj.2,
                         ))
@@ -486,7 +1080,8 @@ fn setup(
                         ));
                 }
                 for obj in &room.left {
-                    let white_matl = materials.add(Color::Hsla(char_to_color(obj.2)));
+                    let white_matl =
+                        materials.add(openings::object_material(&metadata, &colors, obj.2));
                     parent
                         .spawn((
                             Mesh3d(meshes.add(Cuboid::new(scaling * 0.2, scaling, scaling))),
@@ -497,12 +1092,18 @@ fn setup(
                                 scaling * (0.0 - obj.0 as f32 + room.height / 2.0 - 0.5),
                             )),
                             Object(obj.2),
+                            Cell {
+                                room: index,
+                                wall: Wall::Left,
+                                x: obj.0,
+                                y: obj.1,
+                            },
                             bevy::light::NotShadowCaster,
                         ))
                         .observe(update_material_on::<Pointer<Over>>(
                             materials.add(Color::Hsla(Hsla {
                                 lightness: 0.8,
-                                ..char_to_color(obj.2)
+                                ..colors.get(obj.2)
                             })),
                             obj.2,
                         ))
@@ -512,7 +1113,8 @@ fn setup(
                         ));
                 }
                 for obj in &room.floor {
-                    let white_matl = materials.add(Color::Hsla(char_to_color(obj.2)));
+                    let white_matl =
+                        materials.add(openings::object_material(&metadata, &colors, obj.2));
                     parent
                         .spawn((
                             Mesh3d(meshes.add(Cuboid::new(scaling, scaling * 0.2, scaling))),
@@ -523,12 +1125,18 @@ fn setup(
                                 scaling * (obj.1 as f32 - room.depth / 2.0 + 0.5),
                             )),
                             Object(obj.2),
+                            Cell {
+                                room: index,
+                                wall: Wall::Floor,
+                                x: obj.0,
+                                y: obj.1,
+                            },
                             bevy::light::NotShadowCaster,
                         ))
                         .observe(update_material_on::<Pointer<Over>>(
                             materials.add(Color::Hsla(Hsla {
                                 lightness: 0.8,
-                                ..char_to_color(obj.2)
+                                ..colors.get(obj.2)
                             })),
                             obj.2,
                         ))
@@ -539,10 +1147,13 @@ fn setup(
                 }
             });
     }
+    commands.insert_resource(Rooms(rooms));
+    commands.insert_resource(colors);
 
     // Spawn other stuff.
-    commands.spawn(Text(
-        "Hover objects to read their descriptions.".to_string(),
+    commands.spawn((
+        Text("Hover objects to read their descriptions.".to_string()),
+        HoverText,
     ));
     commands.spawn((
         Camera3d::default(),
@@ -560,6 +1171,7 @@ fn setup(
             ..default()
         },
         Transform::from_xyz(5.0, -10.0, 2.5),
+        lighting::FillLight,
     ));
 }
 
@@ -570,13 +1182,18 @@ fn update_material_on<E: EntityEvent>(
 ) -> impl Fn(
     On<E>,
     Query<(&mut MeshMaterial3d<StandardMaterial>, &Object)>,
-    Query<&mut bevy::prelude::Text>,
+    Query<&mut bevy::prelude::Text, With<HoverText>>,
     Res<Descriptions>,
+    Res<inspector::Selection>,
+    Res<search::Search>,
 ) {
-    move |_trigger, mut objects, mut texts, descriptions| {
-        for (mut material, character) in objects.iter_mut() {
-            if character.0 == ch {
-                material.0 = new_material.clone();
+    move |_trigger, mut objects, mut texts, descriptions, selection, search| {
+        // Selected objects and search results stay highlighted.
+        if !selection.contains(ch) && !search.is_active() {
+            for (mut material, character) in objects.iter_mut() {
+                if character.0 == ch {
+                    material.0 = new_material.clone();
+                }
             }
         }
         for mut text in texts.iter_mut() {
@@ -591,6 +1208,7 @@ fn update_material_on<E: EntityEvent>(
     }
 }
 
+/// Colour of a character in the `hash` palette.
 pub fn char_to_color(c: char) -> Hsla {
     let hash = c as u32 * 10007; // Big prime as multiplicator.
     Hsla {
@@ -600,3 +1218,78 @@ pub fn char_to_color(c: char) -> Hsla {
         alpha: 1.0,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An empty room at the origin, measured in cells.
+    pub fn empty_room(width: usize, height: usize, depth: usize) -> Room {
+        Room {
+            name: "Test room".to_string(),
+            level: None,
+            source_line: 1,
+            metadata: HashMap::new(),
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            width: width as f32,
+            height: height as f32,
+            depth: depth as f32,
+            top: Vec::new(),
+            floor: Vec::new(),
+            left: Vec::new(),
+            right: Vec::new(),
+            front: Vec::new(),
+            back: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn wall_cell_is_the_inverse_of_surface_position() {
+        let room = empty_room(5, 4, 3);
+        for wall in Wall::ALL {
+            let (_, _, columns, rows) = wall.net_section(5, 4, 3);
+            for x in 0..columns {
+                for y in 0..rows {
+                    let position = room.surface_position(wall, x, y);
+                    assert_eq!(room.wall_cell(wall, position), (x, y), "{wall}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn edge_cells_count_every_character() {
+        let mut room = empty_room(3, 3, 3);
+        // The top left cell of the back wall is the same point as the left end
+        // of the last row of the top wall and of the last column of the left wall.
+        room.back = vec![(0, 0, 'A')];
+        room.top = vec![(0, 2, 'B'), (1, 2, 'B')];
+        room.left = vec![(2, 0, 'A')];
+        let counts = count_cells(&[room]);
+        assert_eq!(counts[&'A'], 1);
+        assert_eq!(counts[&'B'], 2);
+    }
+
+    #[test]
+    fn unknown_keys_stay_in_the_description() {
+        let (metadata, description) =
+            split_metadata("Wire.\nnote: check with client\n\ntype: wire\nback-material: brick");
+        assert_eq!(metadata["type"], "wire");
+        assert_eq!(metadata["back-material"], "brick");
+        assert!(!metadata.contains_key("note"));
+        assert_eq!(description, "Wire.\nnote: check with client");
+    }
+
+    #[test]
+    fn options_do_not_take_the_input_file() {
+        let args = ["--port-map=ports.csv", "house.md", "--palette=hues"].map(String::from);
+        let (files, options) = parse_args(args).unwrap();
+        assert_eq!(files, ["house.md"]);
+        assert_eq!(options["port-map"], "ports.csv");
+        assert_eq!(options["palette"], "hues");
+        assert!(parse_args(["--port-map", "house.md"].map(String::from)).is_err());
+        assert!(parse_args(["--size=2"].map(String::from)).is_err());
+    }
+}
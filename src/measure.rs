@@ -0,0 +1,267 @@
+use crate::{
+    CELL_SIZE, NEIGHBOURS, Room, RoomShell, Rooms, Settings, Wall, cursor::CursorCell, ui,
+};
+use bevy::{prelude::*, transform::TransformSystems, ui::UiSystems};
+use std::collections::{HashMap, VecDeque};
+
+/// Measures between two clicked cells while the measure mode is on (`R`).
+/// The straight distance and the distance along the walls are shown next to
+/// a dimension line.
+pub struct MeasurePlugin;
+
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Measure::default())
+            .add_systems(PostStartup, spawn_measure_panel)
+            .add_systems(
+                Update,
+                (
+                    toggle_measure.run_if(not(ui::typing)),
+                    pick_point,
+                    update_measure_text,
+                    draw_dimension_line,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                PostUpdate,
+                move_measure_label
+                    .after(TransformSystems::Propagate)
+                    .before(UiSystems::Layout),
+            );
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Measure {
+    pub enabled: bool,
+    /// Clicked points as room and point on its surface.
+    points: Vec<(usize, IVec3)>,
+}
+
+#[derive(Component)]
+struct MeasureText;
+
+#[derive(Component)]
+struct MeasureLabel;
+
+fn spawn_measure_panel(mut commands: Commands, tools: Single<Entity, With<ui::Tools>>) {
+    commands
+        .spawn(ui::tool_panel(*tools))
+        .with_child((MeasureText, Text::new("Measure (R): off")));
+    commands.spawn((
+        MeasureLabel,
+        Text::default(),
+        TextShadow::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            ..default()
+        },
+        UiTransform::from_translation(Val2::percent(-50.0, -100.0)),
+        Visibility::Hidden,
+        Pickable::IGNORE,
+    ));
+}
+
+fn toggle_measure(keys: Res<ButtonInput<KeyCode>>, mut measure: ResMut<Measure>) {
+    if keys.just_pressed(KeyCode::KeyR) {
+        measure.enabled = !measure.enabled;
+        measure.points.clear();
+    }
+}
+
+fn pick_point(
+    mut measure: ResMut<Measure>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    mut cursor: CursorCell,
+    rooms: Res<Rooms>,
+) {
+    if !measure.enabled || !buttons.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cell) = cursor.get() else {
+        return;
+    };
+    if measure.points.len() == 2 {
+        measure.points.clear();
+    }
+    let room = &rooms.0[cell.room];
+    measure
+        .points
+        .push((cell.room, room.surface_position(cell.wall, cell.x, cell.y)));
+}
+
+/// All points on the surface of a room.
+fn surface_positions(room: &Room) -> impl Iterator<Item = IVec3> {
+    let size = IVec3::new(room.width as i32, room.height as i32, room.depth as i32);
+    (0..size.x).flat_map(move |x| {
+        (0..size.y).flat_map(move |y| {
+            (0..size.z)
+                .map(move |z| IVec3::new(x, y, z))
+                .filter(move |p| p.cmpeq(IVec3::ZERO).any() || p.cmpeq(size - 1).any())
+        })
+    })
+}
+
+/// Number of cells walked along the walls from `start` to `end`. The path may
+/// pass from one room to the next through the walls they share.
+fn surface_distance(rooms: &[Room], start: (usize, IVec3), end: (usize, IVec3)) -> Option<usize> {
+    // Points of different rooms on the same spot of a shared wall.
+    let mut spots: HashMap<IVec3, Vec<(usize, IVec3)>> = HashMap::new();
+    for (index, room) in rooms.iter().enumerate() {
+        let size = IVec3::new(room.width as i32, room.height as i32, room.depth as i32);
+        for position in surface_positions(room) {
+            for wall in Wall::ALL {
+                let outside = position + wall.normal();
+                if outside.cmplt(IVec3::ZERO).any() || outside.cmpge(size).any() {
+                    spots
+                        .entry(room.wall_spot(wall, position))
+                        .or_default()
+                        .push((index, position));
+                }
+            }
+        }
+    }
+    let mut doors: HashMap<(usize, IVec3), Vec<(usize, IVec3)>> = HashMap::new();
+    for points in spots.values() {
+        for &a in points {
+            for &b in points {
+                if a.0 != b.0 {
+                    doors.entry(a).or_default().push(b);
+                }
+            }
+        }
+    }
+
+    let mut distances = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        if current == end {
+            return distances.get(&end).copied();
+        }
+        let (index, position) = current;
+        let room = &rooms[index];
+        let size = IVec3::new(room.width as i32, room.height as i32, room.depth as i32);
+        let inside = NEIGHBOURS
+            .iter()
+            .map(|offset| (index, position + offset))
+            .filter(|(_, p)| {
+                p.cmpge(IVec3::ZERO).all()
+                    && p.cmplt(size).all()
+                    && (p.cmpeq(IVec3::ZERO).any() || p.cmpeq(size - 1).any())
+            });
+        let through = doors.get(&current).into_iter().flatten().copied();
+        for next in inside.chain(through).collect::<Vec<_>>() {
+            if !distances.contains_key(&next) {
+                distances.insert(next, distances[&current] + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+fn update_measure_text(
+    measure: Res<Measure>,
+    rooms: Res<Rooms>,
+    settings: Res<Settings>,
+    mut texts: Query<&mut Text, (With<MeasureText>, Without<MeasureLabel>)>,
+    mut labels: Query<&mut Text, With<MeasureLabel>>,
+) {
+    if !measure.is_changed() {
+        return;
+    }
+    let (text, label) = match measure.points[..] {
+        _ if !measure.enabled => ("Measure (R): off".to_string(), String::new()),
+        [] => (
+            "Measure (R): click the first point".to_string(),
+            String::new(),
+        ),
+        [_] => (
+            "Measure (R): click the second point".to_string(),
+            String::new(),
+        ),
+        [(a, start), (b, end), ..] => {
+            let cell_size = settings.cell_size();
+            let straight = rooms.0[a]
+                .world_position(start)
+                .distance(rooms.0[b].world_position(end))
+                * cell_size;
+            let along = surface_distance(&rooms.0, (a, start), (b, end))
+                .map(|cells| format!("{:.2} m", cells as f32 * cell_size))
+                .unwrap_or("-".to_string());
+            (
+                format!("Measure (R): {straight:.2} m straight, {along} along the walls"),
+                format!("{straight:.2} m"),
+            )
+        }
+    };
+    for mut measure_text in &mut texts {
+        measure_text.0 = text.clone();
+    }
+    for mut measure_label in &mut labels {
+        measure_label.0 = label.clone();
+    }
+}
+
+/// Position of a measured point in the scene.
+fn scene_position(
+    rooms: &Rooms,
+    shells: &Query<(&RoomShell, &GlobalTransform)>,
+    (index, position): (usize, IVec3),
+) -> Option<Vec3> {
+    let (_, transform) = shells.iter().find(|(shell, _)| shell.0 == index)?;
+    Some(transform.transform_point(rooms.0[index].local_position(position) * CELL_SIZE))
+}
+
+fn draw_dimension_line(
+    mut gizmos: Gizmos,
+    measure: Res<Measure>,
+    rooms: Res<Rooms>,
+    shells: Query<(&RoomShell, &GlobalTransform)>,
+) {
+    let points: Vec<Vec3> = measure
+        .points
+        .iter()
+        .filter_map(|&point| scene_position(&rooms, &shells, point))
+        .collect();
+    for &point in &points {
+        gizmos.sphere(
+            Isometry3d::from_translation(point),
+            CELL_SIZE * 0.3,
+            Color::WHITE,
+        );
+    }
+    if let [start, end] = points[..] {
+        gizmos.line(start, end, Color::WHITE);
+    }
+}
+
+fn move_measure_label(
+    measure: Res<Measure>,
+    rooms: Res<Rooms>,
+    shells: Query<(&RoomShell, &GlobalTransform)>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    mut labels: Query<(&mut Node, &mut Visibility), With<MeasureLabel>>,
+) {
+    let middle = match measure.points[..] {
+        [a, b] if measure.enabled => scene_position(&rooms, &shells, a)
+            .zip(scene_position(&rooms, &shells, b))
+            .map(|(a, b)| (a + b) / 2.0),
+        _ => None,
+    };
+    let viewport = middle.and_then(|middle| {
+        cameras
+            .iter()
+            .find_map(|(camera, transform)| camera.world_to_viewport(transform, middle).ok())
+    });
+    for (mut node, mut visibility) in &mut labels {
+        let Some(viewport) = viewport else {
+            visibility.set_if_neq(Visibility::Hidden);
+            continue;
+        };
+        visibility.set_if_neq(Visibility::Inherited);
+        node.left = Val::Px(viewport.x);
+        node.top = Val::Px(viewport.y);
+    }
+}
@@ -0,0 +1,276 @@
+use crate::{Metadata, NEIGHBOURS, Room, Rooms, Settings, SurfaceGraph, Wall, get_option};
+use bevy::prelude::*;
+use std::collections::{HashMap, VecDeque, hash_map::Entry};
+
+/// Assigns every port of an `outlet` to a free port of the `patch-panel` its
+/// `cable` leads to. The port map is printed at startup and written to the
+/// file given with `--port-map=...` (`.csv` or `.md`).
+pub struct NetworkPlugin;
+
+impl Plugin for NetworkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, map_ports);
+    }
+}
+
+pub struct PortAssignment {
+    pub room: String,
+    pub wall: Wall,
+    pub outlet: String,
+    /// Cable length in metres.
+    pub length: Option<f32>,
+    pub port: Option<String>,
+}
+
+/// Follows the cables cell by cell from the cells of an outlet to the nearest
+/// patch panel and returns the panel together with the number of cable cells
+/// on the way. A cable drawn on both sides of a wall between two rooms is
+/// counted once.
+fn route_to_panel(
+    (outlet, cells): (char, &[(usize, IVec3)]),
+    graph: &SurfaceGraph,
+    metadata: &Metadata,
+) -> Option<(char, usize)> {
+    let mut distances: HashMap<(usize, IVec3), usize> =
+        cells.iter().map(|&cell| (cell, 0)).collect();
+    let mut queue: VecDeque<(usize, IVec3)> = cells.iter().copied().collect();
+    let mut nearest: Option<(usize, char)> = None;
+    while let Some((room, position)) = queue.pop_front() {
+        let distance = distances[&(room, position)];
+        if nearest.is_some_and(|(length, _)| distance > length) {
+            break;
+        }
+        let walked = |ch: &&char| **ch == outlet || metadata.is_type(**ch, "cable");
+        // The same cell on the other side of a wall is no further away.
+        let through = graph
+            .chars(room, position)
+            .iter()
+            .filter(walked)
+            .flat_map(|&ch| graph.partners(room, position, ch).iter().copied());
+        for next in through.collect::<Vec<_>>() {
+            if let Entry::Vacant(entry) = distances.entry(next) {
+                entry.insert(distance);
+                queue.push_front(next);
+            }
+        }
+        for offset in NEIGHBOURS {
+            let next = (room, position + offset);
+            let chars = graph.chars(next.0, next.1);
+            if let Some(&panel) = chars
+                .iter()
+                .filter(|&&ch| metadata.is_type(ch, "patch-panel"))
+                .min()
+                && nearest.is_none_or(|nearest| (distance, panel) < nearest)
+            {
+                nearest = Some((distance, panel));
+            }
+            if !distances.contains_key(&next)
+                && chars.iter().any(|&ch| metadata.is_type(ch, "cable"))
+            {
+                distances.insert(next, distance + 1);
+                queue.push_back(next);
+            }
+        }
+    }
+    nearest.map(|(length, panel)| (panel, length))
+}
+
+fn number_of_ports(metadata: &Metadata, ch: char, default: f32) -> usize {
+    metadata.number(ch, "ports").unwrap_or(default) as usize
+}
+
+/// Assigns the ports of every outlet in turn. Every region of touching cells
+/// of an outlet character is an outlet of its own. Several regions of one
+/// character are numbered like `O1` and `O2` in the order of the rooms.
+fn assign_ports(rooms: &[Room], metadata: &Metadata, cell_size: f32) -> Vec<PortAssignment> {
+    let graph = SurfaceGraph::new(rooms);
+    let mut free_ports: HashMap<char, std::ops::RangeInclusive<usize>> = metadata
+        .0
+        .keys()
+        .filter(|&&ch| metadata.is_type(ch, "patch-panel"))
+        .map(|&ch| (ch, 1..=number_of_ports(metadata, ch, 24.0)))
+        .collect();
+    let mut outlets: Vec<char> = metadata
+        .0
+        .keys()
+        .copied()
+        .filter(|&ch| metadata.is_type(ch, "outlet"))
+        .collect();
+    outlets.sort();
+
+    let mut assignments = Vec::new();
+    for outlet in outlets {
+        let regions = graph.regions(outlet);
+        for (index, cells) in regions.iter().enumerate() {
+            let (room, position) = cells[0];
+            let Some(wall) = rooms[room].walls().into_iter().find_map(|(wall, objects)| {
+                objects
+                    .iter()
+                    .any(|&(x, y, ch)| {
+                        ch == outlet && rooms[room].surface_position(wall, x, y) == position
+                    })
+                    .then_some(wall)
+            }) else {
+                continue;
+            };
+            let name = if regions.len() == 1 {
+                outlet.to_string()
+            } else {
+                format!("{outlet}{}", index + 1)
+            };
+            let route = route_to_panel((outlet, cells), &graph, metadata);
+            let ports = number_of_ports(metadata, outlet, 1.0);
+            for outlet_port in 1..=ports {
+                let port = route.and_then(|(panel, _)| {
+                    let port = free_ports.get_mut(&panel)?.next()?;
+                    Some(format!("{panel}-{port}"))
+                });
+                assignments.push(PortAssignment {
+                    room: rooms[room].name.clone(),
+                    wall,
+                    outlet: if ports == 1 {
+                        name.clone()
+                    } else {
+                        format!("{name}-{outlet_port}")
+                    },
+                    length: route.map(|(_, length)| length as f32 * cell_size),
+                    port,
+                });
+            }
+        }
+    }
+    assignments
+}
+
+fn map_ports(rooms: Res<Rooms>, metadata: Res<Metadata>, settings: Res<Settings>) {
+    let assignments = assign_ports(&rooms.0, &metadata, settings.cell_size());
+    if assignments.is_empty() {
+        return;
+    }
+
+    let table = port_map_markdown(&assignments);
+    println!("{table}");
+    if let Some(path) = get_option("port-map") {
+        let content = if path.ends_with(".csv") {
+            port_map_csv(&assignments)
+        } else {
+            table
+        };
+        if let Err(error) = std::fs::write(&path, content) {
+            eprintln!("Error: Failed to write port map '{path}': {error}");
+        }
+    }
+}
+
+fn port_map_markdown(assignments: &[PortAssignment]) -> String {
+    let mut table = String::from(
+        "| Room | Wall | Outlet | Cable length | Port |\n|------|------|--------|--------------|------|\n",
+    );
+    for row in assignments {
+        table.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            row.room,
+            row.wall,
+            row.outlet,
+            row.length
+                .map(|length| format!("{length:.1} m"))
+                .unwrap_or("-".to_string()),
+            row.port.as_deref().unwrap_or("-"),
+        ));
+    }
+    table
+}
+
+fn port_map_csv(assignments: &[PortAssignment]) -> String {
+    let quote = |text: &str| format!("\"{}\"", text.replace('"', "\"\""));
+    let mut csv = String::from("room,wall,outlet,cable length (m),port\n");
+    for row in assignments {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            quote(&row.room),
+            row.wall,
+            quote(&row.outlet),
+            row.length
+                .map(|length| format!("{length:.2}"))
+                .unwrap_or_default(),
+            quote(row.port.as_deref().unwrap_or("")),
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::empty_room;
+
+    /// Outlet O, cable K and patch panel P.
+    fn network() -> Metadata {
+        Metadata(HashMap::from([
+            (
+                'O',
+                HashMap::from([("type".to_string(), "outlet".to_string())]),
+            ),
+            (
+                'K',
+                HashMap::from([("type".to_string(), "cable".to_string())]),
+            ),
+            (
+                'P',
+                HashMap::from([("type".to_string(), "patch-panel".to_string())]),
+            ),
+        ]))
+    }
+
+    #[test]
+    fn cable_length_along_the_shortest_path() {
+        let mut room = empty_room(8, 8, 3);
+        // Outlet O, cable K with a branch off the path and patch panel P.
+        room.back = vec![
+            (1, 1, 'O'),
+            (2, 1, 'K'),
+            (3, 1, 'K'),
+            (4, 1, 'K'),
+            (5, 1, 'P'),
+            (3, 2, 'K'),
+            (3, 3, 'K'),
+        ];
+        let metadata = network();
+        let graph = SurfaceGraph::new(&[room]);
+        let outlet = graph.regions('O');
+        assert_eq!(
+            route_to_panel(('O', &outlet[0]), &graph, &metadata),
+            Some(('P', 3))
+        );
+    }
+
+    #[test]
+    fn every_region_of_an_outlet_is_an_outlet() {
+        let mut room = empty_room(8, 8, 3);
+        room.back = vec![
+            (1, 1, 'O'),
+            (2, 1, 'K'),
+            (3, 1, 'K'),
+            (4, 1, 'P'),
+            (1, 5, 'O'),
+            (2, 5, 'K'),
+            (3, 5, 'K'),
+            (4, 5, 'K'),
+            (4, 4, 'K'),
+            (4, 3, 'K'),
+            (4, 2, 'K'),
+        ];
+        let assignments = assign_ports(&[room], &network(), 1.0);
+        let rows: Vec<_> = assignments
+            .iter()
+            .map(|row| (row.outlet.as_str(), row.length, row.port.as_deref()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("O1", Some(6.0), Some("P-1")),
+                ("O2", Some(2.0), Some("P-2"))
+            ]
+        );
+    }
+}
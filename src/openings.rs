@@ -0,0 +1,111 @@
+use crate::{CELL_SIZE, Metadata, Room, Wall, palette::Colors};
+use bevy::{
+    asset::RenderAssetUsages,
+    mesh::{Indices, PrimitiveTopology},
+    prelude::*,
+};
+
+/// Characters of `type: door` and `type: window` are cut out of the walls of
+/// their rooms. Windows are filled with glass unless they have `glass: no`.
+fn is_opening(metadata: &Metadata, ch: char) -> bool {
+    metadata.is_type(ch, "door") || metadata.is_type(ch, "window")
+}
+
+/// Whether a cell of a wall is left open. The floor is never cut open below a
+/// door, but ceilings and floors are cut open for `type: stairs` to make a
+/// stairwell.
+pub fn is_cut(metadata: &Metadata, wall: Wall, ch: char) -> bool {
+    match wall {
+        Wall::Floor => metadata.is_type(ch, "stairs"),
+        Wall::Top => is_opening(metadata, ch) || metadata.is_type(ch, "stairs"),
+        _ => is_opening(metadata, ch),
+    }
+}
+
+/// The given walls of a room as one mesh with a quad per cell, leaving out the
+/// cells of openings. Like a `Cuboid`, the faces point out of the room. Every
+/// wall is covered by the whole texture, unless the texture is repeated every
+/// `texture_size` cells.
+pub fn shell_mesh(
+    room: &Room,
+    metadata: &Metadata,
+    walls: &[Wall],
+    texture_size: Option<f32>,
+) -> Mesh {
+    let size = Vec3::new(room.width, room.height, room.depth);
+    let (mut positions, mut normals, mut uvs, mut indices) = (vec![], vec![], vec![], vec![]);
+    for (wall, objects) in room.walls() {
+        if !walls.contains(&wall) {
+            continue;
+        }
+        let normal = wall.normal();
+        let axis = normal.abs().max_position();
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let (_, _, columns, rows) = wall.net_section(
+            room.width as usize,
+            room.height as usize,
+            room.depth as usize,
+        );
+        for x in 0..columns {
+            for y in 0..rows {
+                if objects
+                    .iter()
+                    .any(|&(ox, oy, ch)| (ox, oy) == (x, y) && is_cut(metadata, wall, ch))
+                {
+                    continue;
+                }
+                let mut center = room.local_position(room.surface_position(wall, x, y));
+                center[axis] = normal[axis] as f32 * size[axis] / 2.0;
+                let mut corners = [(-0.5, -0.5), (0.5, -0.5), (0.5, 0.5), (-0.5, 0.5)].map(
+                    |(du, dv): (f32, f32)| {
+                        let mut corner = center;
+                        corner[u] += du;
+                        corner[v] += dv;
+                        corner
+                    },
+                );
+                // Counter-clockwise seen from outside the room.
+                let edges = (corners[1] - corners[0]).cross(corners[2] - corners[0]);
+                if edges.dot(normal.as_vec3()) < 0.0 {
+                    corners.reverse();
+                }
+                let start = positions.len() as u32;
+                for corner in corners {
+                    let relative = match texture_size {
+                        Some(texture_size) => (corner + size / 2.0) / texture_size,
+                        None => corner / size + 0.5,
+                    };
+                    positions.push(corner * CELL_SIZE);
+                    normals.push(normal.as_vec3());
+                    uvs.push([relative[u], 1.0 - relative[v]]);
+                }
+                indices.extend([start, start + 1, start + 2, start, start + 2, start + 3]);
+            }
+        }
+    }
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_NORMAL, normals)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+/// Material of the objects of a character. Doors are see-through, so they
+/// only show up when hovered or selected, and windows are tinted glass.
+pub fn object_material(metadata: &Metadata, colors: &Colors, ch: char) -> StandardMaterial {
+    let color = colors.get(ch);
+    if !is_opening(metadata, ch) {
+        return Color::Hsla(color).into();
+    }
+    let glass = metadata.is_type(ch, "window") && metadata.get(ch, "glass") != Some("no");
+    StandardMaterial {
+        base_color: Color::Hsla(color.with_alpha(if glass { 0.3 } else { 0.0 })),
+        alpha_mode: AlphaMode::Blend,
+        perceptual_roughness: 0.1,
+        reflectance: 0.5,
+        ..default()
+    }
+}
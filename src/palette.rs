@@ -0,0 +1,92 @@
+use crate::{Metadata, Room, Settings, char_to_color, get_option};
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Palette by Masataka Okabe and Kei Ito, told apart with colour blindness as
+/// long as every colour is used once.
+const OKABE_ITO: [u32; 8] = [
+    0xE69F00, 0x56B4E9, 0x009E73, 0xF0E442, 0x0072B2, 0xD55E00, 0xCC79A7, 0x000000,
+];
+
+/// Palettes by Paul Tol, told apart with colour blindness as well.
+const TOL_BRIGHT: [u32; 7] = [
+    0x4477AA, 0xEE6677, 0x228833, 0xCCBB44, 0x66CCEE, 0xAA3377, 0xBBBBBB,
+];
+const TOL_MUTED: [u32; 10] = [
+    0x332288, 0x88CCEE, 0x44AA99, 0x117733, 0x999933, 0xDDCC77, 0xCC6677, 0x882255, 0xAA4499,
+    0xDDDDDD,
+];
+
+/// Colour of the objects of every character. The palette is chosen with
+/// `palette: ...` in the settings or `--palette=...` on the command line and
+/// a single character can be given its own colour with `color: #RRGGBB`. The
+/// fixed palettes repeat once there are more characters than colours.
+#[derive(Resource)]
+pub struct Colors(HashMap<char, Hsla>);
+
+impl Colors {
+    pub fn new(rooms: &[Room], metadata: &Metadata, settings: &Settings) -> Self {
+        let mut characters: Vec<char> = rooms
+            .iter()
+            .flat_map(|room| room.surface_cells())
+            .map(|(_, ch)| ch)
+            .collect();
+        characters.sort();
+        characters.dedup();
+
+        let name = get_option("palette")
+            .or_else(|| settings.0.get("palette").cloned())
+            .unwrap_or("hash".to_string());
+        let colors: &[u32] = match name.as_str() {
+            "okabe-ito" => &OKABE_ITO,
+            "tol-bright" => &TOL_BRIGHT,
+            "tol-muted" => &TOL_MUTED,
+            "hash" | "hues" => &[],
+            _ => {
+                eprintln!(
+                    "Error: Unknown palette '{name}', expected hash, hues, okabe-ito, tol-bright or tol-muted."
+                );
+                &[]
+            }
+        };
+        let count = characters.len();
+        if !colors.is_empty() && count > colors.len() {
+            eprintln!(
+                "Error: Palette '{name}' has {} colours for {count} characters, so colours repeat.",
+                colors.len()
+            );
+        }
+        let mut map = HashMap::new();
+        for (index, &ch) in characters.iter().enumerate() {
+            let color = if !colors.is_empty() {
+                let rgb = colors[index % colors.len()];
+                Hsla::from(Srgba::rgb_u8(
+                    (rgb >> 16) as u8,
+                    (rgb >> 8) as u8,
+                    rgb as u8,
+                ))
+            } else if name == "hues" {
+                Hsla::hsl(index as f32 * 360.0 / count as f32, 1.0, 0.5)
+            } else {
+                char_to_color(ch)
+            };
+            map.insert(ch, color);
+        }
+        for (&ch, values) in &metadata.0 {
+            let Some(value) = values.get("color") else {
+                continue;
+            };
+            match Srgba::hex(value) {
+                Ok(color) => {
+                    map.insert(ch, color.into());
+                }
+                Err(_) => eprintln!("Error: '{value}' of '{ch}' is not a colour like #E69F00."),
+            }
+        }
+        Colors(map)
+    }
+
+    pub fn get(&self, ch: char) -> Hsla {
+        self.0.get(&ch).copied().unwrap_or(char_to_color(ch))
+    }
+}
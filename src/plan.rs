@@ -0,0 +1,145 @@
+use crate::{CELL_SIZE, RoomShell, Rooms, camera::Flight, explode::Explode, levels::Levels, ui};
+use bevy::{
+    camera::ScalingMode,
+    input::mouse::{AccumulatedMouseMotion, AccumulatedMouseScroll},
+    prelude::*,
+};
+
+/// An orthographic top-down view of the shown level (`P`). Ceilings are hidden
+/// (see `legend`), the rooms are outlined and the objects on the walls show up
+/// on the wall lines. Drag with the right mouse button to pan and scroll to zoom.
+pub struct PlanPlugin;
+
+impl Plugin for PlanPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PlanView(false))
+            .add_systems(
+                Update,
+                (
+                    toggle_plan_view.run_if(not(ui::typing)),
+                    pan_plan_view,
+                    draw_outlines,
+                )
+                    .chain(),
+            )
+            // After the level systems, so the plan view wins over their framing.
+            .add_systems(PostUpdate, fit_plan_view);
+    }
+}
+
+#[derive(Resource)]
+pub struct PlanView(pub bool);
+
+/// Looking down with the back wall at the top.
+const DOWN: Vec3 = Vec3::new(0.0, 1.0, 0.001);
+
+fn toggle_plan_view(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut plan: ResMut<PlanView>,
+    mut levels: ResMut<Levels>,
+    mut cameras: Query<&mut Projection, With<Camera3d>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+    plan.0 = !plan.0;
+    if plan.0 && levels.shown.is_none() && levels.names.len() > 1 {
+        levels.shown = Some(0);
+    }
+    for mut projection in &mut cameras {
+        *projection = if plan.0 {
+            Projection::Orthographic(OrthographicProjection::default_3d())
+        } else {
+            Projection::Perspective(default())
+        };
+    }
+}
+
+/// Frames the shown level whenever the plan view is switched on or the level
+/// changes.
+fn fit_plan_view(
+    mut commands: Commands,
+    plan: Res<PlanView>,
+    levels: Res<Levels>,
+    explode: Res<Explode>,
+    rooms: Res<Rooms>,
+    mut cameras: Query<(Entity, &Transform, &mut Projection), With<Camera3d>>,
+) {
+    if plan.is_added() || !plan.is_changed() && !levels.is_changed() && !explode.is_changed() {
+        return;
+    }
+    let Some((min, max)) = levels.bounds(&rooms.0, &explode) else {
+        return;
+    };
+    for (entity, transform, mut projection) in &mut cameras {
+        if plan.0 {
+            if let Projection::Orthographic(ortho) = projection.as_mut() {
+                ortho.scaling_mode = ScalingMode::AutoMin {
+                    min_width: (max.x - min.x) * 1.1,
+                    min_height: (max.z - min.z) * 1.1,
+                };
+                ortho.scale = 1.0;
+            }
+            let center = (min + max) / 2.0;
+            commands.entity(entity).insert(Flight {
+                translation: center + DOWN.normalize() * (max.y - min.y + 1.0),
+                look_at: center,
+            });
+        } else if plan.is_changed() {
+            let mut flight = Flight::frame(transform, (min, max));
+            flight.translation = flight.look_at
+                + Vec3::ONE.normalize() * flight.translation.distance(flight.look_at);
+            commands.entity(entity).insert(flight);
+        }
+    }
+}
+
+fn pan_plan_view(
+    plan: Res<PlanView>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    motion: Res<AccumulatedMouseMotion>,
+    scroll: Res<AccumulatedMouseScroll>,
+    mut cameras: Query<(&Camera, &mut Transform, &mut Projection), Without<Flight>>,
+) {
+    if !plan.0 {
+        return;
+    }
+    for (camera, mut transform, mut projection) in &mut cameras {
+        let Projection::Orthographic(ortho) = projection.as_mut() else {
+            continue;
+        };
+        if scroll.delta.y != 0.0 {
+            ortho.scale = (ortho.scale * (1.0 - scroll.delta.y.clamp(-1.0, 1.0) * 0.1)).max(0.05);
+        }
+        if buttons.pressed(MouseButton::Right)
+            && let Some(size) = camera.logical_viewport_size()
+        {
+            let per_pixel = ortho.area.width() / size.x;
+            transform.translation.x -= motion.delta.x * per_pixel;
+            transform.translation.z -= motion.delta.y * per_pixel;
+        }
+    }
+}
+
+fn draw_outlines(
+    mut gizmos: Gizmos,
+    plan: Res<PlanView>,
+    rooms: Res<Rooms>,
+    shells: Query<(&RoomShell, &GlobalTransform, &InheritedVisibility)>,
+) {
+    if !plan.0 {
+        return;
+    }
+    for (shell, transform, visibility) in &shells {
+        if !visibility.get() {
+            continue;
+        }
+        let room = &rooms.0[shell.0];
+        let center = transform.translation() + Vec3::Y * room.height * CELL_SIZE / 2.0;
+        gizmos.rect(
+            Isometry3d::new(center, Quat::from_rotation_x(std::f32::consts::FRAC_PI_2)),
+            Vec2::new(room.width, room.depth) * CELL_SIZE,
+            Color::WHITE,
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{
+    CELL_SIZE, Cell, Descriptions, Metadata, Object, Room, Rooms, Settings, Wall,
+    shapes::{straight_direction, surface_sets},
+};
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Renders `pipe` characters as cylinders with their `diameter` (mm). A pipe
+/// with `flow: S` is a drain into the object `S` and must fall towards it by at
+/// least `min-slope` (%) of its horizontal length.
+pub struct PlumbingPlugin;
+
+impl Plugin for PlumbingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, (check_drain_slopes, shape_pipes));
+    }
+}
+
+struct PipeCell {
+    room: usize,
+    wall: Wall,
+    x: usize,
+    y: usize,
+    /// Position in cells relative to the building.
+    position: Vec3,
+}
+
+impl PipeCell {
+    fn describe(&self, rooms: &[Room]) -> String {
+        format!(
+            "in {} on the {} wall at column {}, row {}",
+            rooms[self.room].name, self.wall, self.x, self.y
+        )
+    }
+}
+
+fn cells_of(rooms: &[Room], ch: char) -> Vec<PipeCell> {
+    let mut cells = Vec::new();
+    for (index, room) in rooms.iter().enumerate() {
+        let mut visited = HashSet::new();
+        for (wall, objects) in room.walls() {
+            for &(x, y, _) in objects.iter().filter(|obj| obj.2 == ch) {
+                let position = room.surface_position(wall, x, y);
+                if visited.insert(position) {
+                    cells.push(PipeCell {
+                        room: index,
+                        wall,
+                        x,
+                        y,
+                        position: room.world_position(position),
+                    });
+                }
+            }
+        }
+    }
+    cells
+}
+
+fn adjacent(a: &PipeCell, b: &PipeCell) -> bool {
+    a.position.distance(b.position) < 1.01
+}
+
+/// Checks that a drain falls all the way to `target`: no cell is higher than
+/// the one before it and every horizontal stretch is followed by a drop of at
+/// least `min_slope` (%) of its length.
+fn drain_errors(rooms: &[Room], ch: char, target: char, min_slope: f32) -> Vec<String> {
+    let cells = cells_of(rooms, ch);
+    let target_cells = cells_of(rooms, target);
+
+    // Number of pipe cells between each cell and the target.
+    let mut distances: Vec<Option<usize>> = cells
+        .iter()
+        .map(|cell| {
+            target_cells
+                .iter()
+                .any(|other| adjacent(cell, other))
+                .then_some(0)
+        })
+        .collect();
+    let mut queue: VecDeque<usize> = (0..cells.len())
+        .filter(|&i| distances[i].is_some())
+        .collect();
+    if queue.is_empty() {
+        return vec![format!("Pipe {ch} does not reach {target}.")];
+    }
+    while let Some(i) = queue.pop_front() {
+        for j in 0..cells.len() {
+            if distances[j].is_none() && adjacent(&cells[i], &cells[j]) {
+                distances[j] = distances[i].map(|distance| distance + 1);
+                queue.push_back(j);
+            }
+        }
+    }
+    let downstream = |i: usize| {
+        let distance = distances[i]?.checked_sub(1)?;
+        (0..cells.len()).find(|&j| distances[j] == Some(distance) && adjacent(&cells[i], &cells[j]))
+    };
+
+    let mut errors = Vec::new();
+    for (i, cell) in cells.iter().enumerate() {
+        if distances[i].is_none() {
+            errors.push(format!(
+                "Pipe {ch} is not connected to {target} {}.",
+                cell.describe(rooms)
+            ));
+        } else if let Some(j) = downstream(i)
+            && cells[j].position.y > cell.position.y + 0.01
+        {
+            errors.push(format!(
+                "Pipe {ch} rises towards {target} {}.",
+                cells[j].describe(rooms)
+            ));
+        }
+    }
+
+    // Follow every branch from its upper end and check the fall of each
+    // stretch between two drops, the drop into the target included.
+    let upstream_ends = (0..cells.len()).filter(|&i| {
+        distances[i].is_some()
+            && !(0..cells.len()).any(|j| {
+                distances[j] == distances[i].map(|distance| distance + 1)
+                    && adjacent(&cells[i], &cells[j])
+            })
+    });
+    let mut reported = HashSet::new();
+    for start in upstream_ends {
+        let (mut i, mut stretch_start, mut run) = (start, start, 0);
+        loop {
+            let next = downstream(i);
+            let next_height = match next {
+                Some(j) => cells[j].position.y,
+                None => target_cells
+                    .iter()
+                    .filter(|other| adjacent(&cells[i], other))
+                    .map(|other| other.position.y)
+                    .fold(cells[i].position.y, f32::min),
+            };
+            let drop = cells[i].position.y - next_height;
+            if drop > 0.01 || next.is_none() {
+                if next.is_none() && drop <= 0.01 {
+                    run += 1;
+                }
+                let slope = drop.max(0.0) / run as f32 * 100.0;
+                if run > 0 && slope < min_slope && reported.insert(stretch_start) {
+                    errors.push(format!(
+                        "Pipe {ch} falls {slope:.1} % towards {target} {} but needs {min_slope} %.",
+                        cells[stretch_start].describe(rooms)
+                    ));
+                }
+                run = 0;
+            } else if drop.abs() <= 0.01 {
+                run += 1;
+            } else {
+                // Rises are reported above, the next stretch starts after it.
+                run = 0;
+            }
+            let Some(j) = next else {
+                break;
+            };
+            if run == 0 {
+                stretch_start = j;
+            }
+            i = j;
+        }
+    }
+    errors
+}
+
+fn check_drain_slopes(
+    rooms: Res<Rooms>,
+    metadata: Res<Metadata>,
+    settings: Res<Settings>,
+    mut descriptions: ResMut<Descriptions>,
+) {
+    let mut drains: Vec<(char, char)> = metadata
+        .0
+        .keys()
+        .filter(|&&ch| metadata.is_type(ch, "pipe"))
+        .filter_map(|&ch| Some((ch, metadata.get(ch, "flow")?.chars().next()?)))
+        .collect();
+    drains.sort();
+    for (ch, target) in drains {
+        let min_slope = metadata
+            .number(ch, "min-slope")
+            .unwrap_or(settings.number("min-slope", 1.0));
+        for error in drain_errors(&rooms.0, ch, target, min_slope) {
+            eprintln!("Error: {error}");
+            let description = descriptions.0.entry(ch).or_default();
+            if !description.is_empty() {
+                description.push_str("\n\n");
+            }
+            description.push_str(&error);
+        }
+    }
+}
+
+fn shape_pipes(
+    mut meshes: ResMut<Assets<Mesh>>,
+    rooms: Res<Rooms>,
+    metadata: Res<Metadata>,
+    settings: Res<Settings>,
+    mut pipes: Query<(&Object, &Cell, &mut Mesh3d, &mut Transform)>,
+) {
+    let mut shapes: HashMap<char, (Handle<Mesh>, Handle<Mesh>)> = HashMap::new();
+    let surfaces = surface_sets(&rooms.0);
+    for (object, cell, mut mesh, mut transform) in &mut pipes {
+        if !metadata.is_type(object.0, "pipe") {
+            continue;
+        }
+        let (cylinder, joint) = shapes.entry(object.0).or_insert_with(|| {
+            let diameter = metadata.number(object.0, "diameter").unwrap_or(100.0) / 1000.0;
+            let radius = diameter / 2.0 / settings.cell_size() * CELL_SIZE;
+            (
+                meshes.add(Cylinder::new(radius, CELL_SIZE)),
+                meshes.add(Sphere::new(radius)),
+            )
+        });
+
+        // Straight pieces run along the neighbouring pipe cells, the rest are joints.
+        let position = rooms.0[cell.room].surface_position(cell.wall, cell.x, cell.y);
+        if let Some(direction) = straight_direction(&surfaces[cell.room], position, object.0) {
+            mesh.0 = cylinder.clone();
+            transform.rotation = Quat::from_rotation_arc(Vec3::Y, direction.as_vec3());
+        } else {
+            mesh.0 = joint.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::empty_room;
+
+    /// A room with a pipe `P` draining into `S` drawn on its back wall.
+    fn drain(pipe: &[(usize, usize)], target: (usize, usize)) -> Vec<Room> {
+        let mut room = empty_room(8, 8, 3);
+        room.back = pipe.iter().map(|&(x, y)| (x, y, 'P')).collect();
+        room.back.push((target.0, target.1, 'S'));
+        vec![room]
+    }
+
+    #[test]
+    fn falling_drain() {
+        let rooms = drain(&[(1, 1), (2, 1), (2, 2), (3, 2), (3, 3)], (3, 4));
+        assert!(drain_errors(&rooms, 'P', 'S', 50.0).is_empty());
+    }
+
+    #[test]
+    fn flat_stretch_before_a_drop() {
+        let rooms = drain(&[(1, 1), (2, 1), (3, 1), (4, 1), (4, 2), (4, 3)], (4, 4));
+        let errors = drain_errors(&rooms, 'P', 'S', 50.0);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("falls 33.3 %"));
+        assert!(errors[0].contains("column 1, row 1"));
+    }
+
+    #[test]
+    fn flat_end() {
+        let rooms = drain(&[(1, 1), (1, 2), (2, 2)], (3, 2));
+        let errors = drain_errors(&rooms, 'P', 'S', 1.0);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("column 1, row 2"));
+    }
+
+    #[test]
+    fn rising_drain() {
+        let rooms = drain(&[(1, 3), (2, 3), (2, 2), (3, 2)], (4, 2));
+        let errors = drain_errors(&rooms, 'P', 'S', 1.0);
+        assert!(errors.iter().any(|error| error.contains("rises")));
+    }
+
+    #[test]
+    fn drain_not_reaching_the_target() {
+        let rooms = drain(&[(1, 1), (2, 1)], (5, 5));
+        assert_eq!(
+            drain_errors(&rooms, 'P', 'S', 1.0),
+            ["Pipe P does not reach S."]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use crate::{Descriptions, Object, camera::Flight, ui};
+use bevy::{
+    input_focus::InputFocus,
+    prelude::*,
+    text::{EditableText, TextEdit},
+};
+
+/// A search box matching characters and descriptions. Matching objects are
+/// highlighted and everything else is dimmed. `Enter` flies the camera to the
+/// next hit, `Shift` + `Enter` to the previous one and `Escape` clears the search.
+pub struct SearchPlugin;
+
+impl Plugin for SearchPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Search::default())
+            .add_systems(PostStartup, spawn_search_box)
+            .add_systems(Update, (update_search, cycle_hits).chain());
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Search {
+    query: String,
+    hits: Vec<char>,
+    current: usize,
+}
+
+impl Search {
+    pub fn is_active(&self) -> bool {
+        !self.query.is_empty()
+    }
+
+    pub fn matches(&self, ch: char) -> bool {
+        self.hits.contains(&ch)
+    }
+}
+
+#[derive(Component)]
+struct SearchInput;
+
+#[derive(Component)]
+struct SearchStatus;
+
+fn spawn_search_box(mut commands: Commands) {
+    commands
+        .spawn(ui::panel(Node {
+            left: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(8.0),
+            align_items: AlignItems::Center,
+            ..default()
+        }))
+        .with_children(|parent| {
+            parent.spawn(Text::new("Search"));
+            parent.spawn((
+                SearchInput,
+                EditableText::default(),
+                Node {
+                    width: Val::Px(200.0),
+                    padding: UiRect::horizontal(Val::Px(4.0)),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.1)),
+            ));
+            parent.spawn((SearchStatus, Text::default()));
+        });
+}
+
+fn fly_to_hit(
+    commands: &mut Commands,
+    ch: char,
+    objects: &Query<(&Object, &GlobalTransform)>,
+    cameras: &Query<(Entity, &Transform), With<Camera3d>>,
+) {
+    let cells: Vec<Vec3> = objects
+        .iter()
+        .filter(|(object, _)| object.0 == ch)
+        .map(|(_, transform)| transform.translation())
+        .collect();
+    if cells.is_empty() {
+        return;
+    }
+    let center = cells.iter().sum::<Vec3>() / cells.len() as f32;
+    for (entity, transform) in cameras {
+        commands
+            .entity(entity)
+            .insert(Flight::towards(transform, center, 0.6));
+    }
+}
+
+fn update_search(
+    mut commands: Commands,
+    mut search: ResMut<Search>,
+    descriptions: Res<Descriptions>,
+    inputs: Query<&EditableText, (With<SearchInput>, Changed<EditableText>)>,
+    objects: Query<(&Object, &GlobalTransform)>,
+    cameras: Query<(Entity, &Transform), With<Camera3d>>,
+) {
+    let Ok(input) = inputs.single() else {
+        return;
+    };
+    let query = input.value().to_string().trim().to_lowercase();
+    if query == search.query {
+        return;
+    }
+    let mut hits: Vec<char> = objects.iter().map(|(object, _)| object.0).collect();
+    hits.sort();
+    hits.dedup();
+    hits.retain(|ch| {
+        ch.to_lowercase().eq(query.chars())
+            || descriptions
+                .0
+                .get(ch)
+                .is_some_and(|description| description.to_lowercase().contains(&query))
+    });
+    if query.is_empty() {
+        hits.clear();
+    }
+    *search = Search {
+        query,
+        hits,
+        current: 0,
+    };
+    if let Some(&ch) = search.hits.first() {
+        fly_to_hit(&mut commands, ch, &objects, &cameras);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn cycle_hits(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut focus: ResMut<InputFocus>,
+    mut search: ResMut<Search>,
+    mut inputs: Query<(Entity, &mut EditableText), With<SearchInput>>,
+    mut status: Query<&mut Text, With<SearchStatus>>,
+    objects: Query<(&Object, &GlobalTransform)>,
+    cameras: Query<(Entity, &Transform), With<Camera3d>>,
+) {
+    let Ok((entity, mut input)) = inputs.single_mut() else {
+        return;
+    };
+    if focus.get() == Some(entity) {
+        if keys.just_pressed(KeyCode::Escape) {
+            input.queue_edit(TextEdit::SelectAll);
+            input.queue_edit(TextEdit::Delete);
+            focus.clear();
+        } else if keys.just_pressed(KeyCode::Enter) && !search.hits.is_empty() {
+            let count = search.hits.len();
+            search.current = if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+                (search.current + count - 1) % count
+            } else {
+                (search.current + 1) % count
+            };
+            fly_to_hit(
+                &mut commands,
+                search.hits[search.current],
+                &objects,
+                &cameras,
+            );
+        }
+    }
+    if search.is_changed() {
+        for mut text in &mut status {
+            text.0 = match search.hits.get(search.current) {
+                Some(ch) => format!("{} of {}: {ch}", search.current + 1, search.hits.len()),
+                None if search.is_active() => "No hits".to_string(),
+                None => String::new(),
+            };
+        }
+    }
+}
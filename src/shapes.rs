@@ -0,0 +1,166 @@
+use crate::{CELL_SIZE, Cell, Metadata, NEIGHBOURS, Object, Room, Rooms, Settings};
+use bevy::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Gives the objects of a character a `shape` (`box`, `sphere`, `cylinder`,
+/// `plate` or `tube`) or a glTF `model` from the assets folder, e.g.
+/// `model: socket.glb`. Shapes and models stand on the wall with their Y axis
+/// pointing into the room. Models are measured in metres and drawn in the
+/// colour of their character.
+pub struct ShapePlugin;
+
+impl Plugin for ShapePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, shape_objects)
+            .add_systems(Update, tint_models);
+    }
+}
+
+/// The glTF scene of an object, drawn in the colour of the object so it is
+/// highlighted like the other objects.
+#[derive(Component)]
+struct Model;
+
+/// The objects of every room as points on its surface, for quick lookups.
+pub fn surface_sets(rooms: &[Room]) -> Vec<HashSet<(IVec3, char)>> {
+    rooms
+        .iter()
+        .map(|room| room.surface_cells().into_iter().collect())
+        .collect()
+}
+
+/// The direction of a straight run of `ch` through a point on the surface of
+/// a room, or nothing at ends, corners and forks.
+pub fn straight_direction(
+    cells: &HashSet<(IVec3, char)>,
+    position: IVec3,
+    ch: char,
+) -> Option<IVec3> {
+    let directions: Vec<IVec3> = NEIGHBOURS
+        .into_iter()
+        .filter(|offset| cells.contains(&(position + offset, ch)))
+        .collect();
+    let direction = directions.first()?.abs();
+    (directions.len() <= 2 && directions.iter().all(|other| other.abs() == direction))
+        .then_some(direction)
+}
+
+/// Moves a point along the wall normal to `depth` from the room centre.
+fn at_depth(translation: Vec3, normal: Vec3, depth: f32) -> Vec3 {
+    translation + normal * (depth - translation.dot(normal))
+}
+
+/// Mesh and thickness in cells of a shape.
+fn shape_mesh(shape: &str) -> Option<(Mesh, f32)> {
+    let size = CELL_SIZE * 0.999;
+    match shape {
+        "box" => Some((Cuboid::from_length(size * 0.8).into(), 0.8)),
+        "sphere" => Some((Sphere::new(size * 0.4).into(), 0.8)),
+        "cylinder" => Some((Cylinder::new(size * 0.4, size * 0.5).into(), 0.5)),
+        "plate" => Some((Cuboid::new(size, size * 0.2, size).into(), 0.2)),
+        "tube" => Some((Cylinder::new(size * 0.15, size).into(), 0.3)),
+        "tube joint" => Some((Sphere::new(size * 0.15).into(), 0.3)),
+        _ => None,
+    }
+}
+
+fn shape_objects(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    asset_server: Res<AssetServer>,
+    rooms: Res<Rooms>,
+    metadata: Res<Metadata>,
+    settings: Res<Settings>,
+    mut objects: Query<(Entity, &Object, &Cell, &mut Mesh3d, &mut Transform)>,
+) {
+    let mut shapes: HashMap<String, Option<(Handle<Mesh>, f32)>> = HashMap::new();
+    let surfaces = surface_sets(&rooms.0);
+    for (entity, object, cell, mut mesh, mut transform) in &mut objects {
+        // Pipes get their shape from their diameter.
+        if metadata.is_type(object.0, "pipe") {
+            continue;
+        }
+        let room = &rooms.0[cell.room];
+        let normal = cell.wall.normal().as_vec3();
+        let into_room = Quat::from_rotation_arc(Vec3::Y, -normal);
+        // Distance of the wall from the room centre.
+        let wall = normal
+            .abs()
+            .dot(Vec3::new(room.width, room.height, room.depth))
+            / 2.0
+            * CELL_SIZE;
+        if let Some(model) = metadata.get(object.0, "model") {
+            transform.translation = at_depth(transform.translation, normal, wall);
+            commands.entity(entity).remove::<Mesh3d>().with_child((
+                Model,
+                WorldAssetRoot(
+                    asset_server.load(GltfAssetLabel::Scene(0).from_asset(model.to_string())),
+                ),
+                Transform {
+                    rotation: into_room,
+                    scale: Vec3::splat(CELL_SIZE / settings.cell_size()),
+                    ..default()
+                },
+            ));
+            continue;
+        }
+        let Some(shape) = metadata.get(object.0, "shape") else {
+            continue;
+        };
+        let mut rotation = into_room;
+        let mut shape = shape.to_string();
+        if shape == "tube" {
+            let position = room.surface_position(cell.wall, cell.x, cell.y);
+            match straight_direction(&surfaces[cell.room], position, object.0) {
+                Some(direction) => {
+                    rotation = Quat::from_rotation_arc(Vec3::Y, direction.as_vec3());
+                }
+                None => shape = "tube joint".to_string(),
+            }
+        }
+        let Some((shape_mesh, thickness)) = shapes
+            .entry(shape.clone())
+            .or_insert_with(|| {
+                let shape_mesh = shape_mesh(&shape);
+                if shape_mesh.is_none() {
+                    eprintln!(
+                        "Error: Unknown shape '{shape}' of '{}', expected box, sphere, cylinder, plate or tube.",
+                        object.0
+                    );
+                }
+                shape_mesh.map(|(shape_mesh, thickness)| (meshes.add(shape_mesh), thickness))
+            })
+        else {
+            continue;
+        };
+        mesh.0 = shape_mesh.clone();
+        transform.rotation = rotation;
+        // Standing on the wall, inside the room.
+        transform.translation = at_depth(
+            transform.translation,
+            normal,
+            wall - *thickness / 2.0 * CELL_SIZE,
+        );
+    }
+}
+
+/// Gives the meshes of the loaded models the current material of their object.
+fn tint_models(
+    objects: Query<&MeshMaterial3d<StandardMaterial>, With<Object>>,
+    models: Query<(Entity, &ChildOf), With<Model>>,
+    children: Query<&Children>,
+    mut meshes: Query<&mut MeshMaterial3d<StandardMaterial>, Without<Object>>,
+) {
+    for (model, child_of) in &models {
+        let Ok(material) = objects.get(child_of.parent()) else {
+            continue;
+        };
+        for entity in children.iter_descendants(model) {
+            if let Ok(mut mesh_material) = meshes.get_mut(entity)
+                && mesh_material.0 != material.0
+            {
+                mesh_material.0 = material.0.clone();
+            }
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use crate::{Metadata, Room, Rooms, Settings, Wall, cutaway::crosses_box, ui};
+use bevy::{
+    light::CascadeShadowConfigBuilder,
+    prelude::*,
+    ui_widgets::{SliderRange, SliderValue},
+};
+
+/// Lights the building by the sun (`T`) for a day of the year and a time of
+/// day chosen with the sliders. The sun is placed by the `latitude` (degrees)
+/// and the `north` direction (degrees clockwise from the back wall) in the
+/// settings. The hours of direct sunlight through the windows of every room on
+/// the `date` (`MM-DD`) of the settings are printed at startup.
+pub struct SunPlugin;
+
+impl Plugin for SunPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostStartup,
+            (insert_sun, (spawn_sun_panel, report_daylight_hours)).chain(),
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_sun.run_if(not(ui::typing)),
+                read_sun_sliders,
+                move_sun,
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct Sun {
+    pub enabled: bool,
+    /// Day of the year, counted from 0.
+    day: f32,
+    /// Solar time in hours.
+    hours: f32,
+    latitude: f32,
+    north: f32,
+}
+
+/// First day of every month, ignoring leap years.
+const MONTH_STARTS: [u32; 12] = [0, 31, 59, 90, 120, 151, 181, 212, 243, 273, 304, 334];
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+impl Sun {
+    /// Direction towards the sun in the scene at a solar time, below the
+    /// horizon at night.
+    fn direction(&self, hours: f32) -> Vec3 {
+        let latitude = self.latitude.to_radians();
+        let declination =
+            -23.44_f32.to_radians() * (std::f32::consts::TAU / 365.0 * (self.day + 10.0)).cos();
+        let hour_angle = (15.0 * (hours - 12.0)).to_radians();
+        let altitude = (latitude.sin() * declination.sin()
+            + latitude.cos() * declination.cos() * hour_angle.cos())
+        .asin();
+        // Clockwise from north.
+        let azimuth = hour_angle
+            .sin()
+            .atan2(hour_angle.cos() * latitude.sin() - declination.tan() * latitude.cos())
+            + std::f32::consts::PI;
+        let angle = azimuth + self.north.to_radians();
+        Vec3::new(
+            altitude.cos() * angle.sin(),
+            altitude.sin(),
+            -altitude.cos() * angle.cos(),
+        )
+    }
+
+    fn date(&self) -> String {
+        let day = self.day as u32;
+        let month = MONTH_STARTS
+            .iter()
+            .rposition(|&start| start <= day)
+            .unwrap_or(0);
+        format!("{} {}", day - MONTH_STARTS[month] + 1, MONTHS[month])
+    }
+}
+
+#[derive(Component)]
+struct SunLight;
+
+#[derive(Component)]
+struct SunText;
+
+#[derive(Component)]
+struct DaySlider;
+
+#[derive(Component)]
+struct HourSlider;
+
+/// Day of the year of a date like `06-21` or `2026-06-21`, counted without
+/// leap days.
+fn day_of_year(date: &str) -> Option<f32> {
+    let mut parts = date.rsplit('-');
+    let day: u32 = parts.next()?.parse().ok()?;
+    let month = parts.next()?.parse::<usize>().ok()?.checked_sub(1)?;
+    let start = *MONTH_STARTS.get(month)?;
+    let end = MONTH_STARTS.get(month + 1).copied().unwrap_or(365);
+    (1..=end - start)
+        .contains(&day)
+        .then(|| (start + day - 1) as f32)
+}
+
+fn insert_sun(mut commands: Commands, settings: Res<Settings>) {
+    let date = settings.0.get("date").map_or("06-21", String::as_str);
+    let day = day_of_year(date).unwrap_or_else(|| {
+        eprintln!("Error: Date '{date}' is not like 06-21.");
+        171.0
+    });
+    commands.insert_resource(Sun {
+        enabled: false,
+        day,
+        hours: 12.0,
+        latitude: settings.number("latitude", 50.0),
+        north: settings.number("north", 0.0),
+    });
+    commands.spawn((
+        SunLight,
+        DirectionalLight {
+            shadow_maps_enabled: true,
+            ..default()
+        },
+        CascadeShadowConfigBuilder {
+            first_cascade_far_bound: 1.0,
+            maximum_distance: 10.0,
+            ..default()
+        }
+        .build(),
+        Visibility::Hidden,
+    ));
+}
+
+fn spawn_sun_panel(mut commands: Commands, tools: Single<Entity, With<ui::Tools>>, sun: Res<Sun>) {
+    commands
+        .spawn(ui::tool_panel(*tools))
+        .with_children(|panel| {
+            panel.spawn((SunText, Text::new("Sun (T): off")));
+            panel.spawn((DaySlider, ui::slider(sun.day, SliderRange::new(0.0, 364.0))));
+            panel.spawn((
+                HourSlider,
+                ui::slider(sun.hours, SliderRange::new(0.0, 24.0)),
+            ));
+        });
+}
+
+/// Hours of the day with the sun shining through a window of the room, in
+/// steps of six minutes.
+fn daylight_hours(rooms: &[Room], metadata: &Metadata, sun: &Sun, index: usize) -> f32 {
+    let room = &rooms[index];
+    let windows: Vec<(Vec3, Vec3)> = room
+        .walls()
+        .into_iter()
+        .filter(|(wall, _)| *wall != Wall::Floor)
+        .flat_map(|(wall, objects)| {
+            objects
+                .iter()
+                .filter(|obj| metadata.is_type(obj.2, "window"))
+                .map(move |&(x, y, _)| {
+                    let normal = wall.normal().as_vec3();
+                    let position = room.world_position(room.surface_position(wall, x, y));
+                    (position + normal * 0.51, normal)
+                })
+        })
+        .collect();
+    let sunny = (0..240).filter(|step| {
+        let direction = sun.direction(*step as f32 / 10.0);
+        direction.y > 0.0
+            && windows.iter().any(|&(start, normal)| {
+                direction.dot(normal) > 0.0
+                    && !rooms.iter().enumerate().any(|(other, room)| {
+                        other != index
+                            && crosses_box(
+                                start,
+                                start + direction * 1000.0,
+                                Vec3::new(room.x, room.y, room.z),
+                                Vec3::new(room.width, room.height, room.depth) / 2.0,
+                            )
+                    })
+            })
+    });
+    sunny.count() as f32 / 10.0
+}
+
+fn report_daylight_hours(rooms: Res<Rooms>, metadata: Res<Metadata>, sun: Res<Sun>) {
+    if !metadata.0.keys().any(|&ch| metadata.is_type(ch, "window")) {
+        return;
+    }
+    for (index, room) in rooms.0.iter().enumerate() {
+        let hours = daylight_hours(&rooms.0, &metadata, &sun, index);
+        println!(
+            "Room {}: {hours:.1} h of direct sunlight through the windows on {}.",
+            room.name,
+            sun.date()
+        );
+    }
+}
+
+fn toggle_sun(keys: Res<ButtonInput<KeyCode>>, mut sun: ResMut<Sun>) {
+    if keys.just_pressed(KeyCode::KeyT) {
+        sun.enabled = !sun.enabled;
+    }
+}
+
+fn read_sun_sliders(
+    mut sun: ResMut<Sun>,
+    days: Query<&SliderValue, (With<DaySlider>, Changed<SliderValue>)>,
+    hours: Query<&SliderValue, (With<HourSlider>, Changed<SliderValue>)>,
+) {
+    for value in &days {
+        sun.day = value.0.round();
+    }
+    for value in &hours {
+        sun.hours = value.0;
+    }
+}
+
+fn move_sun(
+    sun: Res<Sun>,
+    mut lights: Query<(&mut Transform, &mut Visibility), With<SunLight>>,
+    mut texts: Query<&mut Text, With<SunText>>,
+) {
+    if !sun.is_changed() {
+        return;
+    }
+    let direction = sun.direction(sun.hours);
+    for (mut transform, mut visibility) in &mut lights {
+        *visibility = if sun.enabled && direction.y > 0.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        let up = if direction.y.abs() < 0.99 {
+            Vec3::Y
+        } else {
+            Vec3::Z
+        };
+        *transform = Transform::default().looking_to(-direction, up);
+    }
+    for mut text in &mut texts {
+        text.0 = if sun.enabled {
+            format!(
+                "Sun (T): {}, {:02}:{:02}, {:.0}° high",
+                sun.date(),
+                sun.hours as u32,
+                (sun.hours.fract() * 60.0) as u32,
+                direction.y.asin().to_degrees()
+            )
+        } else {
+            "Sun (T): off".to_string()
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_of_the_year() {
+        assert_eq!(day_of_year("01-01"), Some(0.0));
+        assert_eq!(day_of_year("06-21"), Some(171.0));
+        assert_eq!(day_of_year("2026-12-31"), Some(364.0));
+    }
+
+    #[test]
+    fn invalid_dates() {
+        assert_eq!(day_of_year("13-01"), None);
+        assert_eq!(day_of_year("00-10"), None);
+        assert_eq!(day_of_year("June"), None);
+        assert_eq!(day_of_year("02-31"), None);
+        assert_eq!(day_of_year("01-00"), None);
+        assert_eq!(day_of_year("12-40"), None);
+    }
+}
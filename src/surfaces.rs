@@ -0,0 +1,183 @@
+use crate::{Metadata, RoomShell, Rooms, Settings, Wall, openings::shell_mesh};
+use bevy::{
+    asset::RenderAssetUsages,
+    image::{ImageAddressMode, ImageLoaderSettings, ImageSampler, ImageSamplerDescriptor},
+    picking::mesh_picking::ray_cast::RayCastBackfaces,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use std::collections::HashMap;
+
+/// Covers the walls of a room with a material given in the room section:
+/// `material: ...` for all walls, `walls-material: ...` for the four sides and
+/// `floor-material: ...`, `top-material: ...`, `back-material: ...` and so on
+/// for single walls. A material is one of `tiles`, `wood`, `concrete`, `brick`
+/// and `plaster` or an image in the assets folder covering 1 m × 1 m.
+pub struct SurfacePlugin;
+
+impl Plugin for SurfacePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, apply_surfaces);
+    }
+}
+
+/// The walls of a room with a material of their own. The other walls stay part
+/// of the mesh of the [`RoomShell`].
+#[derive(Component)]
+pub struct Surface;
+
+/// Size of the built-in textures in pixels.
+const PIXELS: u32 = 64;
+
+fn noise(x: u32, y: u32) -> f32 {
+    let hash = (x.wrapping_mul(374761393) ^ y.wrapping_mul(668265263)).wrapping_mul(1274126177);
+    (hash >> 16) as f32 / 65535.0
+}
+
+/// Colour of a pixel of a built-in texture.
+type Pattern = fn(u32, u32) -> [f32; 3];
+
+/// Pattern and real size in metres of a built-in texture.
+fn pattern(name: &str) -> Option<(Pattern, f32)> {
+    let pattern: Pattern = match name {
+        // Two by two tiles of 30 cm.
+        "tiles" => |x, y| {
+            if x % 32 < 2 || y % 32 < 2 {
+                [0.6, 0.6, 0.6]
+            } else {
+                [0.92, 0.93, 0.95].map(|c| c - noise(x, y) * 0.03)
+            }
+        },
+        // Four planks of 20 cm.
+        "wood" => |x, y| {
+            let plank = y / 16;
+            if y % 16 == 0 || (x + plank * 23) % 64 == 0 {
+                return [0.25, 0.15, 0.08];
+            }
+            let grain = ((x as f32 * 0.2 + plank as f32 * 3.0).sin() * 0.5 + 0.5) * 0.08;
+            let shade = noise(plank, 0) * 0.1 - grain;
+            [0.6 + shade, 0.4 + shade, 0.22 + shade / 2.0]
+        },
+        "concrete" => |x, y| [0.55 + noise(x, y) * 0.1; 3],
+        // Bricks of 25 cm × 6.25 cm in running bond.
+        "brick" => |x, y| {
+            let row = y / 8;
+            let x = x + row % 2 * 16;
+            if y % 8 == 0 || x % 32 == 0 {
+                return [0.75, 0.73, 0.7];
+            }
+            let shade = noise(x / 32, row) * 0.15;
+            [0.6 + shade, 0.27 + shade / 2.0, 0.2]
+        },
+        "plaster" => |x, y| [0.93 + noise(x, y) * 0.04; 3],
+        _ => return None,
+    };
+    let size = match name {
+        "tiles" => 0.6,
+        "wood" => 0.8,
+        "brick" => 0.5,
+        _ => 1.0,
+    };
+    Some((pattern, size))
+}
+
+fn repeating() -> ImageSampler {
+    ImageSampler::Descriptor(ImageSamplerDescriptor {
+        address_mode_u: ImageAddressMode::Repeat,
+        address_mode_v: ImageAddressMode::Repeat,
+        ..ImageSamplerDescriptor::linear()
+    })
+}
+
+/// Texture and its size in metres of a built-in material or an image path.
+fn texture(
+    name: &str,
+    images: &mut Assets<Image>,
+    asset_server: &AssetServer,
+) -> (Handle<Image>, f32) {
+    let Some((pattern, size)) = pattern(name) else {
+        let image = asset_server
+            .load_builder()
+            .with_settings(|settings: &mut ImageLoaderSettings| {
+                settings.sampler = repeating();
+            })
+            .load(name.to_string());
+        return (image, 1.0);
+    };
+    let data = (0..PIXELS * PIXELS)
+        .flat_map(|i| {
+            let [r, g, b] = pattern(i % PIXELS, i / PIXELS);
+            [r, g, b, 1.0].map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8)
+        })
+        .collect();
+    let mut image = Image::new(
+        Extent3d {
+            width: PIXELS,
+            height: PIXELS,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image.sampler = repeating();
+    (images.add(image), size)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_surfaces(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut images: ResMut<Assets<Image>>,
+    asset_server: Res<AssetServer>,
+    rooms: Res<Rooms>,
+    metadata: Res<Metadata>,
+    settings: Res<Settings>,
+    mut shells: Query<(Entity, &RoomShell, &mut Mesh3d)>,
+) {
+    let mut textures: HashMap<String, (Handle<Image>, f32)> = HashMap::new();
+    for (entity, shell, mut mesh) in &mut shells {
+        let room = &rooms.0[shell.0];
+        let mut walls_of: HashMap<&str, Vec<Wall>> = HashMap::new();
+        let mut plain = Vec::new();
+        for wall in Wall::ALL {
+            let sides = !matches!(wall, Wall::Top | Wall::Floor);
+            let name = room
+                .metadata
+                .get(&format!("{wall}-material"))
+                .or(room.metadata.get("walls-material").filter(|_| sides))
+                .or(room.metadata.get("material"));
+            match name {
+                Some(name) => walls_of.entry(name).or_default().push(wall),
+                None => plain.push(wall),
+            }
+        }
+        if walls_of.is_empty() {
+            continue;
+        }
+        mesh.0 = meshes.add(shell_mesh(room, &metadata, &plain, None));
+        for (name, walls) in walls_of {
+            let (texture, size) = textures
+                .entry(name.to_string())
+                .or_insert_with(|| texture(name, &mut images, &asset_server))
+                .clone();
+            let texture_size = size / settings.cell_size();
+            commands.spawn((
+                Surface,
+                Mesh3d(meshes.add(shell_mesh(room, &metadata, &walls, Some(texture_size)))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color_texture: Some(texture),
+                    cull_mode: Some(bevy::render::render_resource::Face::Front),
+                    double_sided: true,
+                    ..default()
+                })),
+                Pickable::IGNORE,
+                RayCastBackfaces,
+                bevy::light::NotShadowCaster,
+                ChildOf(entity),
+            ));
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use bevy::{
+    input_focus::InputFocus,
+    prelude::*,
+    text::EditableText,
+    ui::Checked,
+    ui_widgets::{
+        Checkbox, Slider, SliderRange, SliderThumb, SliderValue, TrackClick, checkbox_self_update,
+        observe, slider_self_update,
+    },
+};
+
+/// Keeps the widgets built with the helpers below up to date.
+pub struct WidgetPlugin;
+
+impl Plugin for WidgetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_tools)
+            .add_systems(PostUpdate, (move_slider_thumbs, fill_checkboxes));
+    }
+}
+
+/// Background of the panels, the same as the code editor of the web page.
+pub const PANEL_COLOR: Color = Color::srgba(0.0, 0.106, 0.2, 0.9);
+
+const THUMB_SIZE: f32 = 12.0;
+
+/// An absolutely positioned panel. Its content is added as children.
+pub fn panel(position: Node) -> impl Bundle {
+    (
+        Node {
+            position_type: PositionType::Absolute,
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..position
+        },
+        BackgroundColor(PANEL_COLOR),
+    )
+}
+
+/// Column in the bottom right corner holding the panels of the view tools.
+#[derive(Component)]
+pub struct Tools;
+
+fn spawn_tools(mut commands: Commands) {
+    commands.spawn((
+        Tools,
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(8.0),
+            ..default()
+        },
+    ));
+}
+
+/// A panel in the [`Tools`] column.
+pub fn tool_panel(tools: Entity) -> impl Bundle {
+    (
+        Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        BackgroundColor(PANEL_COLOR),
+        ChildOf(tools),
+    )
+}
+
+/// A horizontal slider. Read its [`SliderValue`] to follow changes.
+pub fn slider(value: f32, range: SliderRange) -> impl Bundle {
+    (
+        Node {
+            width: Val::Px(160.0),
+            height: Val::Px(THUMB_SIZE),
+            align_items: AlignItems::Center,
+            ..default()
+        },
+        Slider {
+            track_click: TrackClick::Snap,
+            ..default()
+        },
+        SliderValue(value),
+        range,
+        observe(slider_self_update),
+        children![
+            (
+                Node {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(4.0),
+                    ..default()
+                },
+                BackgroundColor(Color::srgba(1.0, 1.0, 1.0, 0.3)),
+            ),
+            // The thumb travels the width of the slider minus its own width.
+            (
+                Node {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(0.0),
+                    right: Val::Px(THUMB_SIZE),
+                    top: Val::Px(0.0),
+                    bottom: Val::Px(0.0),
+                    ..default()
+                },
+                children![(
+                    SliderThumb,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        width: Val::Px(THUMB_SIZE),
+                        height: Val::Px(THUMB_SIZE),
+                        ..default()
+                    },
+                    BackgroundColor(Color::WHITE),
+                )],
+            ),
+        ],
+    )
+}
+
+/// A checkbox in `color`. Add [`Checked`] to check it and read [`Checked`] to
+/// follow changes.
+pub fn checkbox(color: Color) -> impl Bundle {
+    (
+        Checkbox,
+        observe(checkbox_self_update),
+        Node {
+            width: Val::Px(14.0),
+            height: Val::Px(14.0),
+            border: UiRect::all(Val::Px(2.0)),
+            ..default()
+        },
+        BorderColor::all(color),
+        BackgroundColor(Color::NONE),
+    )
+}
+
+/// A checked box is filled with the colour of its border.
+fn fill_checkboxes(
+    mut checkboxes: Query<(Has<Checked>, &BorderColor, &mut BackgroundColor), With<Checkbox>>,
+) {
+    for (checked, border, mut background) in &mut checkboxes {
+        background.set_if_neq(BackgroundColor(if checked {
+            border.top
+        } else {
+            Color::NONE
+        }));
+    }
+}
+
+fn move_slider_thumbs(
+    sliders: Query<(Entity, &SliderValue, &SliderRange), Changed<SliderValue>>,
+    children: Query<&Children>,
+    mut thumbs: Query<&mut Node, With<SliderThumb>>,
+) {
+    for (slider, value, range) in &sliders {
+        for child in children.iter_descendants(slider) {
+            if let Ok(mut thumb) = thumbs.get_mut(child) {
+                thumb.left = Val::Percent(range.thumb_position(value.0) * 100.0);
+            }
+        }
+    }
+}
+
+/// Whether a text input has the keyboard focus, so shortcuts must be ignored.
+pub fn typing(focus: Res<InputFocus>, inputs: Query<(), With<EditableText>>) -> bool {
+    focus.get().is_some_and(|entity| inputs.contains(entity))
+}
@@ -0,0 +1,134 @@
+use crate::{Descriptions, Metadata, Rooms, Settings, count_cells};
+use bevy::prelude::*;
+
+/// Computes the voltage drop of every `wire` with a `load` (W) and a
+/// `cross-section` (mm²). The length of a wire is taken from the sketches and
+/// `cell-size`. Wires exceeding `max-voltage-drop` (%) are flagged.
+pub struct VoltagePlugin;
+
+impl Plugin for VoltagePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(PostStartup, report_voltage_drop);
+    }
+}
+
+/// Resistivity of copper in Ω·mm²/m.
+const COPPER_RESISTIVITY: f32 = 0.0178;
+
+pub struct VoltageDrop {
+    pub length: f32,
+    pub volts: f32,
+    pub percent: f32,
+}
+
+impl VoltageDrop {
+    /// Voltage drop of a single-phase circuit, counting the way to the load and
+    /// back, or what is wrong with the values.
+    pub fn new(length: f32, load: f32, cross_section: f32, voltage: f32) -> Result<Self, String> {
+        if !(load.is_finite() && load >= 0.0) {
+            return Err(format!("load {load} W is not zero or more"));
+        }
+        if !(cross_section.is_finite() && cross_section > 0.0) {
+            return Err(format!(
+                "cross-section {cross_section} mm² is not above zero"
+            ));
+        }
+        if !(voltage.is_finite() && voltage > 0.0) {
+            return Err(format!("voltage {voltage} V is not above zero"));
+        }
+        let current = load / voltage;
+        let volts = 2.0 * length * current * COPPER_RESISTIVITY / cross_section;
+        Ok(VoltageDrop {
+            length,
+            volts,
+            percent: volts / voltage * 100.0,
+        })
+    }
+}
+
+fn report_voltage_drop(
+    rooms: Res<Rooms>,
+    metadata: Res<Metadata>,
+    settings: Res<Settings>,
+    mut descriptions: ResMut<Descriptions>,
+) {
+    let max_drop = settings.number("max-voltage-drop", 3.0);
+    let mut wires: Vec<_> = count_cells(&rooms.0)
+        .into_iter()
+        .filter(|(ch, _)| metadata.is_type(*ch, "wire"))
+        .collect();
+    wires.sort();
+    for (ch, cells) in wires {
+        if metadata.get(ch, "load").is_none() && metadata.get(ch, "cross-section").is_none() {
+            continue;
+        }
+        let number = |key: &str| {
+            let value = metadata.get(ch, key)?;
+            let number = value.parse().ok();
+            if number.is_none() {
+                eprintln!("Error: Wire {ch} has {key} '{value}', which is not a number.");
+            }
+            number
+        };
+        let (Some(load), Some(cross_section)) = (number("load"), number("cross-section")) else {
+            eprintln!("Error: Wire {ch} needs both a load and a cross-section.");
+            continue;
+        };
+        let voltage = match metadata.get(ch, "voltage") {
+            Some(_) => number("voltage").unwrap_or(f32::NAN),
+            None => settings.number("voltage", 230.0),
+        };
+        let drop = match VoltageDrop::new(
+            cells as f32 * settings.cell_size(),
+            load,
+            cross_section,
+            voltage,
+        ) {
+            Ok(drop) => drop,
+            Err(error) => {
+                eprintln!("Error: Wire {ch} has a {error}.");
+                continue;
+            }
+        };
+        let mut report = format!(
+            "{:.1} m, {load} W, {cross_section} mm², voltage drop {:.2} V ({:.2} %).",
+            drop.length, drop.volts, drop.percent
+        );
+        if drop.percent > max_drop {
+            report.push_str(&format!(" Exceeds the maximum of {max_drop} %!"));
+        }
+        println!("Wire {ch}: {report}");
+        let description = descriptions.0.entry(ch).or_default();
+        if !description.is_empty() {
+            description.push_str("\n\n");
+        }
+        description.push_str(&report);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drop_of_a_ten_ampere_circuit() {
+        let drop = VoltageDrop::new(10.0, 2300.0, 1.5, 230.0).unwrap();
+        assert!((drop.volts - 2.3733).abs() < 0.001);
+        assert!((drop.percent - 1.0319).abs() < 0.001);
+    }
+
+    #[test]
+    fn no_drop_without_load() {
+        let drop = VoltageDrop::new(10.0, 0.0, 1.5, 230.0).unwrap();
+        assert_eq!(drop.volts, 0.0);
+    }
+
+    #[test]
+    fn invalid_values_are_errors() {
+        assert!(VoltageDrop::new(10.0, 100.0, 0.0, 230.0).is_err());
+        assert!(VoltageDrop::new(10.0, 100.0, -1.5, 230.0).is_err());
+        assert!(VoltageDrop::new(10.0, 100.0, 1.5, 0.0).is_err());
+        assert!(VoltageDrop::new(10.0, 100.0, 1.5, f32::NAN).is_err());
+        assert!(VoltageDrop::new(10.0, -100.0, 1.5, 230.0).is_err());
+    }
+}
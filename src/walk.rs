@@ -0,0 +1,377 @@
+use crate::{
+    CELL_SIZE, Metadata, RoomShell, Rooms, Settings, Wall,
+    camera::{Flight, Orbit},
+    ui,
+};
+use bevy::{input::mouse::AccumulatedMouseMotion, prelude::*};
+use std::collections::HashSet;
+
+/// Walks through the building at eye level (`V`): `W`, `A`, `S` and `D` walk,
+/// `Shift` runs, `Space` jumps and dragging with the right mouse button looks
+/// around. Walls can only be passed at `type: door` cells. `type: stairs`
+/// cells on the side walls are steps and the stairwells in ceilings and floors
+/// lead to the rooms above and below.
+pub struct WalkPlugin;
+
+impl Plugin for WalkPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Walk::default())
+            .add_systems(PostStartup, (insert_walkable, spawn_walk_panel))
+            .add_systems(Update, (toggle_walk.run_if(not(ui::typing)), walk).chain());
+    }
+}
+
+/// Size of the person in metres.
+const EYE_HEIGHT: f32 = 1.6;
+const HEIGHT: f32 = 1.8;
+const RADIUS: f32 = 0.2;
+/// Highest step climbed without jumping, in metres.
+const STEP_HEIGHT: f32 = 0.3;
+/// Speeds in metres per second.
+const WALK_SPEED: f32 = 1.4;
+const RUN_SPEED: f32 = 3.0;
+const JUMP_SPEED: f32 = 3.0;
+const GRAVITY: f32 = 9.81;
+
+#[derive(Resource, Default)]
+pub struct Walk {
+    pub enabled: bool,
+    /// Position of the feet in the scene.
+    feet: Vec3,
+    /// Upwards speed in scene units per second.
+    fall_speed: f32,
+    yaw: f32,
+    pitch: f32,
+}
+
+/// Doors, steps and stairwells of every room as points on its surface.
+#[derive(Resource)]
+struct Walkable {
+    doors: Vec<HashSet<(Wall, IVec3)>>,
+    steps: Vec<Vec<IVec3>>,
+    /// Open cells of the ceiling and the floor by column and depth.
+    stairwells: Vec<HashSet<(Wall, IVec2)>>,
+}
+
+#[derive(Component)]
+struct WalkText;
+
+/// Box of a room in the scene.
+struct RoomBox {
+    index: usize,
+    min: Vec3,
+    max: Vec3,
+}
+
+impl RoomBox {
+    /// Size of the room in cells.
+    fn size(&self) -> IVec3 {
+        ((self.max - self.min) / CELL_SIZE).round().as_ivec3()
+    }
+
+    /// The point on the surface lattice of the room nearest to `point`.
+    fn lattice(&self, point: Vec3) -> IVec3 {
+        ((point - self.min) / CELL_SIZE - 0.5)
+            .round()
+            .as_ivec3()
+            .clamp(IVec3::ZERO, self.size() - 1)
+    }
+
+    /// Cells along `axis` whose centres lie between `from` and `to`, or the
+    /// cell of `from` and `to` if they are closer than a cell. Both ends are
+    /// given in the scene.
+    fn cells_between(&self, axis: usize, from: f32, to: f32) -> std::ops::RangeInclusive<i32> {
+        let start = ((from - self.min[axis]) / CELL_SIZE - 0.5).ceil() as i32;
+        let end = ((to - self.min[axis]) / CELL_SIZE - 0.5).floor() as i32;
+        let middle = (((from + to) / 2.0 - self.min[axis]) / CELL_SIZE - 0.5).round() as i32;
+        let last = self.size()[axis] - 1;
+        start.min(middle).clamp(0, last)..=end.max(middle).clamp(0, last)
+    }
+
+    fn contains(&self, point: Vec3) -> bool {
+        point.cmpge(self.min).all() && point.cmplt(self.max).all()
+    }
+
+    fn contains_horizontally(&self, point: Vec3) -> bool {
+        self.contains(point.with_y(self.min.y))
+    }
+}
+
+/// Scene units per metre and the boxes of all rooms.
+struct Building<'a> {
+    scale: f32,
+    walkable: &'a Walkable,
+    boxes: Vec<RoomBox>,
+}
+
+impl Building<'_> {
+    fn step_height(&self) -> f32 {
+        (STEP_HEIGHT * self.scale).max(CELL_SIZE * 1.01)
+    }
+
+    /// Steps below and above the feet in the column of `point`.
+    fn steps_at(&self, point: Vec3) -> impl Iterator<Item = (f32, f32)> + '_ {
+        self.boxes
+            .iter()
+            .filter(move |room| room.contains_horizontally(point))
+            .flat_map(move |room| {
+                let cell = room.lattice(point);
+                self.walkable.steps[room.index]
+                    .iter()
+                    .filter(move |step| step.x == cell.x && step.z == cell.z)
+                    .map(|step| {
+                        let bottom = room.min.y + step.y as f32 * CELL_SIZE;
+                        (bottom, bottom + CELL_SIZE)
+                    })
+            })
+    }
+
+    /// Height of the floor or step the feet at `point` stand on.
+    fn ground(&self, point: Vec3) -> f32 {
+        let outside = self
+            .boxes
+            .iter()
+            .map(|room| room.min.y)
+            .fold(f32::INFINITY, f32::min);
+        let floors = self.boxes.iter().filter_map(|room| {
+            let cell = room.lattice(point);
+            let open = self.walkable.stairwells[room.index]
+                .contains(&(Wall::Floor, IVec2::new(cell.x, cell.z)));
+            (room.contains_horizontally(point) && !open).then_some(room.min.y)
+        });
+        let steps = self.steps_at(point).map(|(_, top)| top);
+        floors
+            .chain(steps)
+            .filter(|&height| height <= point.y + self.step_height())
+            .fold(outside, f32::max)
+    }
+
+    /// Height of the ceiling above the feet at `point`.
+    fn ceiling(&self, point: Vec3) -> f32 {
+        self.boxes
+            .iter()
+            .filter(|room| {
+                let cell = room.lattice(point);
+                room.contains(point + Vec3::Y * CELL_SIZE * 0.5)
+                    && !self.walkable.stairwells[room.index]
+                        .contains(&(Wall::Top, IVec2::new(cell.x, cell.z)))
+            })
+            .map(|room| room.max.y)
+            .fold(f32::INFINITY, f32::min)
+    }
+
+    /// Whether walls or steps that are too high stop the feet going from
+    /// `from` to `to`. A wall can only be passed where the whole body, from
+    /// the feet to the head and from side to side, fits into door cells.
+    fn blocked(&self, from: Vec3, to: Vec3) -> bool {
+        let direction = (to - from).normalize_or_zero();
+        let radius = RADIUS * self.scale;
+        // Just above the floor, so the room below does not count.
+        let from = from + Vec3::Y * CELL_SIZE * 0.5;
+        let lead = to + Vec3::Y * CELL_SIZE * 0.5 + direction * radius;
+        let feet = to.y;
+        // The horizontal axis along the wall.
+        let side = if direction.x.abs() > direction.z.abs() {
+            2
+        } else {
+            0
+        };
+        for room in &self.boxes {
+            let leaving = room.contains(from);
+            if leaving == room.contains(lead) {
+                continue;
+            }
+            let normal = if leaving { direction } else { -direction };
+            let Some(wall) = Wall::facing(normal.round().as_ivec3()) else {
+                return true;
+            };
+            let cell = room.lattice(lead);
+            let columns = room.cells_between(side, lead[side] - radius, lead[side] + radius);
+            let rows = room.cells_between(1, feet, feet + HEIGHT * self.scale);
+            for column in columns {
+                for row in rows.clone() {
+                    let mut door = cell.with_y(row);
+                    door[side] = column;
+                    if !self.walkable.doors[room.index].contains(&(wall, door)) {
+                        return true;
+                    }
+                }
+            }
+        }
+        self.steps_at(to).any(|(bottom, top)| {
+            top > feet + self.step_height() && bottom < feet + HEIGHT * self.scale
+        })
+    }
+}
+
+fn insert_walkable(mut commands: Commands, rooms: Res<Rooms>, metadata: Res<Metadata>) {
+    let mut walkable = Walkable {
+        doors: Vec::new(),
+        steps: Vec::new(),
+        stairwells: Vec::new(),
+    };
+    for room in &rooms.0 {
+        let (mut doors, mut steps, mut stairwells) = (HashSet::new(), Vec::new(), HashSet::new());
+        for (wall, objects) in room.walls() {
+            for &(x, y, ch) in objects {
+                let position = room.surface_position(wall, x, y);
+                if metadata.is_type(ch, "door") {
+                    doors.insert((wall, position));
+                } else if !metadata.is_type(ch, "stairs") {
+                    continue;
+                } else if matches!(wall, Wall::Top | Wall::Floor) {
+                    stairwells.insert((wall, IVec2::new(position.x, position.z)));
+                } else {
+                    steps.push(position);
+                }
+            }
+        }
+        walkable.doors.push(doors);
+        walkable.steps.push(steps);
+        walkable.stairwells.push(stairwells);
+    }
+    commands.insert_resource(walkable);
+}
+
+fn spawn_walk_panel(mut commands: Commands, tools: Single<Entity, With<ui::Tools>>) {
+    commands
+        .spawn(ui::tool_panel(*tools))
+        .with_child((WalkText, Text::new("Walk (V): off")));
+}
+
+fn room_boxes(rooms: &Rooms, shells: &Query<(&RoomShell, &GlobalTransform)>) -> Vec<RoomBox> {
+    shells
+        .iter()
+        .map(|(shell, transform)| {
+            let room = &rooms.0[shell.0];
+            let half_size = Vec3::new(room.width, room.height, room.depth) * CELL_SIZE / 2.0;
+            RoomBox {
+                index: shell.0,
+                min: transform.translation() - half_size,
+                max: transform.translation() + half_size,
+            }
+        })
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn toggle_walk(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut walk: ResMut<Walk>,
+    mut orbit: ResMut<Orbit>,
+    rooms: Res<Rooms>,
+    settings: Res<Settings>,
+    shells: Query<(&RoomShell, &GlobalTransform)>,
+    cameras: Query<(Entity, &Transform, &Projection), With<Camera3d>>,
+    mut texts: Query<&mut Text, With<WalkText>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyV) {
+        return;
+    }
+    walk.enabled = !walk.enabled;
+    for (entity, transform, projection) in &cameras {
+        if !walk.enabled || matches!(projection, Projection::Orthographic(_)) {
+            walk.enabled = false;
+            continue;
+        }
+        // Start on the floor of the room the camera is in or the nearest room.
+        let camera = transform.translation;
+        let boxes = room_boxes(&rooms, &shells);
+        let Some(room) = boxes.iter().min_by(|a, b| {
+            let distance = |room: &RoomBox| camera.distance(camera.clamp(room.min, room.max));
+            distance(a).total_cmp(&distance(b))
+        }) else {
+            walk.enabled = false;
+            continue;
+        };
+        let margin = (RADIUS * CELL_SIZE / settings.cell_size()).min(CELL_SIZE / 2.0);
+        walk.feet = camera
+            .clamp(room.min + margin, room.max - margin)
+            .with_y(room.min.y);
+        walk.fall_speed = 0.0;
+        let (yaw, pitch, _) = transform.rotation.to_euler(EulerRot::YXZ);
+        (walk.yaw, walk.pitch) = (yaw, pitch);
+        orbit.enabled = false;
+        commands.entity(entity).remove::<Flight>();
+    }
+    for mut text in &mut texts {
+        text.0 = format!("Walk (V): {}", if walk.enabled { "on" } else { "off" });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn walk(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    motion: Res<AccumulatedMouseMotion>,
+    mut walk: ResMut<Walk>,
+    walkable: Res<Walkable>,
+    rooms: Res<Rooms>,
+    settings: Res<Settings>,
+    shells: Query<(&RoomShell, &GlobalTransform)>,
+    mut cameras: Query<&mut Transform, (With<Camera3d>, Without<Flight>)>,
+) {
+    if !walk.enabled {
+        return;
+    }
+    let building = Building {
+        scale: CELL_SIZE / settings.cell_size(),
+        walkable: &walkable,
+        boxes: room_boxes(&rooms, &shells),
+    };
+    let scale = building.scale;
+    let delta = time.delta_secs();
+
+    if buttons.pressed(MouseButton::Right) {
+        walk.yaw -= motion.delta.x * 0.003;
+        walk.pitch = (walk.pitch - motion.delta.y * 0.003).clamp(-1.5, 1.5);
+    }
+    let rotation = Quat::from_rotation_y(walk.yaw);
+    let mut input = Vec3::ZERO;
+    for (key, direction) in [
+        (KeyCode::KeyW, Vec3::NEG_Z),
+        (KeyCode::KeyS, Vec3::Z),
+        (KeyCode::KeyA, Vec3::NEG_X),
+        (KeyCode::KeyD, Vec3::X),
+    ] {
+        if keys.pressed(key) {
+            input += direction;
+        }
+    }
+    let speed = if keys.pressed(KeyCode::ShiftLeft) {
+        RUN_SPEED
+    } else {
+        WALK_SPEED
+    };
+    let step = rotation * input.normalize_or_zero() * speed * scale * delta;
+    // Each axis on its own, so the walls can be slid along.
+    for axis in [Vec3::X, Vec3::Z] {
+        let to = walk.feet + step * axis;
+        if step.dot(axis) != 0.0 && !building.blocked(walk.feet, to) {
+            walk.feet = to;
+        }
+    }
+
+    let ground = building.ground(walk.feet);
+    if keys.just_pressed(KeyCode::Space) && walk.feet.y <= ground {
+        walk.fall_speed = JUMP_SPEED * scale;
+    }
+    walk.fall_speed -= GRAVITY * scale * delta;
+    walk.feet.y += walk.fall_speed * delta;
+    if walk.feet.y <= ground {
+        walk.feet.y = ground;
+        walk.fall_speed = 0.0;
+    }
+    let ceiling = building.ceiling(walk.feet);
+    if walk.feet.y + HEIGHT * scale > ceiling {
+        walk.feet.y = (ceiling - HEIGHT * scale).max(ground);
+        walk.fall_speed = walk.fall_speed.min(0.0);
+    }
+
+    for mut transform in &mut cameras {
+        transform.translation = walk.feet + Vec3::Y * EYE_HEIGHT * scale;
+        transform.rotation = Quat::from_euler(EulerRot::YXZ, walk.yaw, walk.pitch, 0.0);
+    }
+}